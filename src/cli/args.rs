@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser};
 
 #[derive(Parser, Debug)]
 #[command(name = "ffmpreg")]
@@ -12,15 +12,86 @@ pub struct Cli {
 	#[arg(short, long)]
 	pub output: String,
 
-	#[arg(long, num_args = 1..)]
-	pub audio: Vec<String>,
+	// container of the input, needed on stdin when probing cannot tell (raw pcm)
+	#[arg(long)]
+	pub format: Option<String>,
 
+	// what headerless input holds, raw pcm needs `codec=`, `sample_rate=` and `channels=`
 	#[arg(long, num_args = 1..)]
-	pub video: Vec<String>,
+	pub input_audio: Vec<String>,
 
-	#[arg(long, num_args = 1..)]
-	pub subtitle: Vec<String>,
+	// container of the output, stdout gets the input's one unless this is set
+	#[arg(long)]
+	pub output_format: Option<String>,
+
+	#[arg(long = "audio", id = "audio", num_args = 1.., action = ArgAction::Append)]
+	audio_values: Vec<String>,
+
+	#[arg(long = "video", id = "video", num_args = 1.., action = ArgAction::Append)]
+	video_values: Vec<String>,
+
+	#[arg(long = "subtitle", id = "subtitle", num_args = 1.., action = ArgAction::Append)]
+	subtitle_values: Vec<String>,
+
+	// one option block per occurrence, `track=N` limits a block to one track
+	#[arg(skip)]
+	pub audio: Vec<Vec<String>>,
+
+	#[arg(skip)]
+	pub video: Vec<Vec<String>>,
+
+	#[arg(skip)]
+	pub subtitle: Vec<Vec<String>>,
 
 	#[arg(long, num_args = 1..)]
 	pub apply: Vec<String>,
+
+	// ffmpeg style stream selection: "0:a:1", "0:v", "-0:s"
+	#[arg(long, allow_hyphen_values = true)]
+	pub map: Vec<String>,
+
+	// input range, given as `-ss`, `-t` and `-to` like ffmpeg
+	#[arg(long = "ss", value_name = "TIME")]
+	pub seek: Option<String>,
+
+	#[arg(short = 't', value_name = "DURATION")]
+	pub duration: Option<String>,
+
+	#[arg(long = "to", value_name = "TIME")]
+	pub to: Option<String>,
+
+	// tag edits, `key=` removes a tag and `--metadata-clear` drops the input's tags first
+	#[arg(long, value_name = "KEY=VALUE")]
+	pub metadata: Vec<String>,
+
+	#[arg(long)]
+	pub metadata_clear: bool,
+}
+
+impl Cli {
+	// derive flattens repeated options, the blocks are split back up by occurrence here
+	pub fn parse_args() -> Self {
+		let matches = Self::command().get_matches_from(std::env::args().map(long_option));
+		let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+		cli.audio = occurrences(&matches, "audio");
+		cli.video = occurrences(&matches, "video");
+		cli.subtitle = occurrences(&matches, "subtitle");
+		cli
+	}
+}
+
+// clap has no multi letter short options, ffmpeg's spelling is mapped to the long one
+fn long_option(arg: String) -> String {
+	match arg.as_str() {
+		"-ss" => "--ss".to_string(),
+		"-to" => "--to".to_string(),
+		_ => arg,
+	}
+}
+
+fn occurrences(matches: &ArgMatches, id: &str) -> Vec<Vec<String>> {
+	match matches.get_occurrences::<String>(id) {
+		Some(occurrences) => occurrences.map(|values| values.cloned().collect()).collect(),
+		None => Vec::new(),
+	}
 }
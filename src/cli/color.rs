@@ -10,21 +10,21 @@ pub const COLOR_WHITE: &str = "\x1b[37m";
 pub fn print_error(message: impl std::fmt::Display) {
 	let message = format!("{}{}{}", COLOR_WHITE, message, COLOR_RESET);
 	let tag = format!("{}error: {}", COLOR_RED, COLOR_RESET);
-	println!("{}{}", tag, message);
+	eprintln!("{}{}", tag, message);
 }
 
 pub fn print_warning(message: impl std::fmt::Display) {
 	let message = format!("{}{}{}", COLOR_WHITE, message, COLOR_RESET);
 	let tag = format!("{}warning: {}", COLOR_YELLOW, COLOR_RESET);
-	println!("{}{}", tag, message);
+	eprintln!("{}{}", tag, message);
 }
 
 pub fn print_success(message: Option<String>) {
 	if let Some(message) = message {
 		let message = format!("{}{}{}", COLOR_WHITE, message, COLOR_RESET);
 		let tag = format!("{}ok: {}", COLOR_YELLOW, COLOR_RESET);
-		println!("{}{}", tag, message);
+		eprintln!("{}{}", tag, message);
 	}
 	let tag = format!("{}ok.{}", COLOR_GREEN, COLOR_RESET);
-	println!("{}", tag);
+	eprintln!("{}", tag);
 }
@@ -1,15 +1,68 @@
 use super::track::parse_track_id;
-use crate::{cli::config::parse_flags, io::Result};
+use crate::{
+	cli::config::{overlay, parse_flags},
+	core::frame::ChannelLayout,
+	io::{Error, Result},
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct AudioConfig {
 	pub track: Option<usize>,
 	pub codec: Option<String>,
 	pub channels: Option<String>,
+	pub matrix: Option<String>,
 	pub sample_rate: Option<String>,
+	pub resampler: Option<String>,
 	pub volume: Option<String>,
 }
 
+impl AudioConfig {
+	// blocks without a track apply to every track, then the ones naming `track` override them
+	pub fn for_track(configs: &[Self], track: usize) -> Self {
+		let mut config = Self { track: Some(track), ..Self::default() };
+		let general = configs.iter().filter(|block| block.track.is_none());
+		for block in general.chain(configs.iter().filter(|block| block.track == Some(track))) {
+			config.overlay(block);
+		}
+		config
+	}
+
+	fn overlay(&mut self, other: &Self) {
+		overlay(&mut self.codec, &other.codec);
+		overlay(&mut self.channels, &other.channels);
+		overlay(&mut self.matrix, &other.matrix);
+		overlay(&mut self.sample_rate, &other.sample_rate);
+		overlay(&mut self.resampler, &other.resampler);
+		overlay(&mut self.volume, &other.volume);
+	}
+
+	// accepts "mono", "stereo", "5.1" or a channel count
+	pub fn parse_channels(&self) -> Result<Option<ChannelLayout>> {
+		self.channels.as_deref().map(ChannelLayout::parse).transpose()
+	}
+
+	// accepts "48000", "48k" or "44.1khz"
+	pub fn parse_sample_rate(&self) -> Result<Option<u32>> {
+		let Some(value) = &self.sample_rate else {
+			return Ok(None);
+		};
+
+		let lower = value.to_lowercase();
+		let number = lower.strip_suffix("hz").unwrap_or(&lower);
+		let (number, scale) = match number.strip_suffix('k') {
+			Some(number) => (number, 1000.0),
+			None => (number, 1.0),
+		};
+
+		match number.parse::<f64>() {
+			Ok(rate) if rate * scale >= 1.0 && rate * scale <= u32::MAX as f64 => {
+				Ok(Some((rate * scale).round() as u32))
+			}
+			_ => Err(Error::invalid_data(format!("invalid sample rate '{}'", value))),
+		}
+	}
+}
+
 pub fn parse_audio(tokens: Vec<String>) -> Result<AudioConfig> {
 	let map = parse_flags(tokens, false);
 	let track = parse_track_id(&map)?;
@@ -18,7 +71,9 @@ pub fn parse_audio(tokens: Vec<String>) -> Result<AudioConfig> {
 		track,
 		codec: map.get("codec").cloned(),
 		channels: map.get("channels").cloned(),
+		matrix: map.get("matrix").cloned(),
 		sample_rate: map.get("sample_rate").cloned(),
+		resampler: map.get("resampler").cloned(),
 		volume: map.get("volume").cloned(),
 	})
 }
@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+use super::Track;
+use crate::core::stream::{Stream, StreamKind};
+use crate::io::{Error, Result};
+
+// one `--map` argument: "0", "0:a", "0:a:1", "0:2", or any of them negated with a leading '-'
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamMap {
+	pub exclude: bool,
+	pub kind: Option<StreamKind>,
+	// relative to `kind` when it is set, otherwise the absolute stream index
+	pub index: Option<usize>,
+}
+
+impl StreamMap {
+	pub fn parse(value: &str) -> Result<Self> {
+		let invalid = || Error::invalid_data(format!("invalid map '{}'", value));
+		let (exclude, spec) = match value.trim().strip_prefix('-') {
+			Some(spec) => (true, spec),
+			None => (false, value.trim()),
+		};
+
+		let mut parts = spec.split(':');
+		let input = parts.next().unwrap_or_default().parse::<usize>().map_err(|_| invalid())?;
+		if input != 0 {
+			return Err(Error::invalid_data(format!("input {} does not exist", input)));
+		}
+
+		let mut map = Self { exclude, kind: None, index: None };
+		if let Some(part) = parts.next() {
+			match part {
+				"a" => map.kind = Some(StreamKind::Audio),
+				"v" => map.kind = Some(StreamKind::Video),
+				"s" => map.kind = Some(StreamKind::Subtitle),
+				index => map.index = Some(index.parse().map_err(|_| invalid())?),
+			}
+		}
+		if let Some(part) = parts.next() {
+			if map.kind.is_none() {
+				return Err(invalid());
+			}
+			map.index = Some(part.parse().map_err(|_| invalid())?);
+		}
+		if parts.next().is_some() {
+			return Err(invalid());
+		}
+		Ok(map)
+	}
+
+	// indexes of the matching streams, a missing index is reported as out of bounds
+	fn resolve(&self, streams: &[Stream]) -> Result<Vec<usize>> {
+		let candidates: Vec<usize> = match self.kind {
+			Some(kind) => streams.iter().filter(|s| s.kind == kind).map(|s| s.index).collect(),
+			None => streams.iter().map(|stream| stream.index).collect(),
+		};
+		let Some(index) = self.index else {
+			return Ok(candidates);
+		};
+		let tracks = Track::One(index).resolve(candidates.len())?;
+		Ok(tracks.into_iter().map(|track| candidates[track]).collect())
+	}
+}
+
+impl Display for StreamMap {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}0", if self.exclude { "-" } else { "" })?;
+		match self.kind {
+			Some(StreamKind::Audio) => write!(f, ":a")?,
+			Some(StreamKind::Video) => write!(f, ":v")?,
+			Some(StreamKind::Subtitle) => write!(f, ":s")?,
+			None => {}
+		}
+		match self.index {
+			Some(index) => write!(f, ":{}", index),
+			None => Ok(()),
+		}
+	}
+}
+
+pub fn parse_maps(values: Vec<String>) -> Result<Vec<StreamMap>> {
+	values.iter().map(|value| StreamMap::parse(value)).collect()
+}
+
+// input stream indexes in output order, negative maps drop streams whatever their position
+pub fn select_streams(maps: &[StreamMap], streams: &[Stream]) -> Result<Vec<usize>> {
+	let mut selected = Vec::new();
+	let positive: Vec<_> = maps.iter().filter(|map| !map.exclude).collect();
+	if positive.is_empty() {
+		selected.extend(streams.iter().map(|stream| stream.index));
+	}
+	for map in positive {
+		let matched = map.resolve(streams)?;
+		if matched.is_empty() {
+			return Err(Error::invalid_data(format!("map '{}' matches no streams", map)));
+		}
+		selected.extend(matched);
+	}
+
+	for map in maps.iter().filter(|map| map.exclude) {
+		let excluded = map.resolve(streams)?;
+		selected.retain(|index| !excluded.contains(index));
+	}
+
+	if selected.is_empty() {
+		return Err(Error::invalid_data("no streams selected"));
+	}
+	Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::time::Time;
+
+	fn streams() -> Vec<Stream> {
+		let kinds = [StreamKind::Video, StreamKind::Audio, StreamKind::Audio, StreamKind::Subtitle];
+		let stream = |(index, kind)| Stream::new(0, index, kind, String::new(), Time::new(1, 1000));
+		kinds.into_iter().enumerate().map(stream).collect()
+	}
+
+	#[test]
+	fn test_select_reorders_and_drops() {
+		let select = |specs: &[&str]| {
+			let maps = parse_maps(specs.iter().map(|spec| spec.to_string()).collect())?;
+			select_streams(&maps, &streams())
+		};
+
+		assert_eq!(select(&[]).unwrap(), vec![0, 1, 2, 3]);
+		assert_eq!(select(&["0:a:1", "0:v"]).unwrap(), vec![2, 0]);
+		assert_eq!(select(&["-0:s"]).unwrap(), vec![0, 1, 2]);
+		assert_eq!(select(&["0", "-0:a:0"]).unwrap(), vec![0, 2, 3]);
+		assert_eq!(select(&["0:3", "0:a"]).unwrap(), vec![3, 1, 2]);
+
+		let error = select(&["0:a:2"]).unwrap_err();
+		assert_eq!(error.to_string(), "track 2 out of bounds (max 1)");
+		let maps = [StreamMap::parse("0:s").unwrap()];
+		let error = select_streams(&maps, &streams()[..3]).unwrap_err();
+		assert_eq!(error.to_string(), "map '0:s' matches no streams");
+		assert!(select(&["1:a"]).is_err());
+		assert!(select(&["0:x"]).is_err());
+	}
+}
@@ -0,0 +1,75 @@
+use crate::container::wav::tags;
+use crate::core::metadata::Metadata;
+use crate::io::{Error, Result};
+
+// `--metadata key=value` edits on top of the input's tags, an empty value removes the tag
+#[derive(Debug, Clone, Default)]
+pub struct MetadataConfig {
+	// drops everything read from the input before the edits, broadcast and cue data included
+	pub clear: bool,
+	pub tags: Vec<(String, String)>,
+}
+
+impl MetadataConfig {
+	pub fn apply(&self, metadata: Option<Metadata>) -> Option<Metadata> {
+		let metadata = match metadata {
+			Some(_) if self.clear => Metadata::new(),
+			None if self.tags.is_empty() => return None,
+			metadata => metadata.unwrap_or_default(),
+		};
+		let mut metadata = metadata;
+		for (key, value) in &self.tags {
+			match value.is_empty() {
+				true => metadata.remove(key),
+				false => metadata.set(key, value.clone()),
+			}
+		}
+		Some(metadata)
+	}
+}
+
+// keys are case insensitive, a wav INFO id like "IENG" names the same tag as "engineer"
+pub fn parse_metadata(values: Vec<String>, clear: bool) -> Result<MetadataConfig> {
+	let mut edits = Vec::with_capacity(values.len());
+	for value in values {
+		let Some((key, value)) = value.split_once('=') else {
+			return Err(Error::invalid_data(format!("--metadata expects key=value, got '{}'", value)));
+		};
+		if key.is_empty() {
+			return Err(Error::invalid_data("--metadata key cannot be empty"));
+		}
+		let key = match key.len() == 4 && key.to_lowercase().starts_with('i') {
+			true => tags::info_key(&key.to_uppercase()),
+			false => key.to_lowercase(),
+		};
+		edits.push((key, value.to_string()));
+	}
+	Ok(MetadataConfig { clear, tags: edits })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tags(values: &[&str]) -> Vec<String> {
+		values.iter().map(|value| value.to_string()).collect()
+	}
+
+	#[test]
+	fn test_metadata_edits() {
+		let mut input = Metadata::new();
+		input.set("title", "old".to_string());
+		input.set("engineer", "sam".to_string());
+
+		let config = parse_metadata(tags(&["Title=new", "IENG=", "date=2026"]), false).unwrap();
+		let output = config.apply(Some(input.clone())).unwrap();
+		assert_eq!(output.title(), Some("new"));
+		assert_eq!(output.get("engineer"), None);
+		assert_eq!(output.get("date"), Some("2026"));
+
+		let cleared = parse_metadata(tags(&["artist=me"]), true).unwrap().apply(Some(input)).unwrap();
+		assert_eq!(cleared.all_fields().len(), 1);
+		assert!(parse_metadata(Vec::new(), false).unwrap().apply(None).is_none());
+		assert!(parse_metadata(tags(&["title"]), false).is_err());
+	}
+}
@@ -1,4 +1,7 @@
 pub mod audio;
+pub mod map;
+pub mod metadata;
+pub mod range;
 pub mod subtitle;
 pub mod track;
 pub mod transform;
@@ -8,6 +11,9 @@ use rustc_hash::FxHashMap;
 pub use track::Track;
 
 pub use audio::{AudioConfig, parse_audio};
+pub use map::{StreamMap, parse_maps, select_streams};
+pub use metadata::{MetadataConfig, parse_metadata};
+pub use range::parse_range;
 pub use subtitle::{SubtitleConfig, parse_subtitle};
 pub use transform::{TransformConfig, parse_transform};
 pub use video::{VideoConfig, parse_video};
@@ -23,3 +29,10 @@ pub fn parse_flags(tokens: Vec<String>, boolean_value: bool) -> FxHashMap<String
 	}
 	map
 }
+
+// a value set in a later option block wins over an earlier one
+pub fn overlay(target: &mut Option<String>, value: &Option<String>) {
+	if value.is_some() {
+		target.clone_from(value);
+	}
+}
@@ -0,0 +1,59 @@
+use crate::core::time::Timestamp;
+use crate::io::{Error, Result};
+use crate::transform::{Trim, TrimEnd};
+
+// `-ss`, `-t` and `-to` as one cut of the input, `-to` is a position in the input like `-ss`
+pub fn parse_range(
+	seek: Option<String>,
+	duration: Option<String>,
+	to: Option<String>,
+) -> Result<Option<Trim>> {
+	let parse = |value: Option<String>| value.as_deref().map(Timestamp::parse).transpose();
+	let (seek, duration, to) = (parse(seek)?, parse(duration)?, parse(to)?);
+
+	let end = match (duration, to) {
+		(Some(_), Some(_)) => return Err(Error::invalid_data("-t and -to cannot be used together")),
+		(Some(duration), None) => Some(TrimEnd::After(duration)),
+		(None, Some(to)) => Some(TrimEnd::At(to)),
+		(None, None) => None,
+	};
+	if seek.is_none() && end.is_none() {
+		return Ok(None);
+	}
+
+	let start = seek.unwrap_or(Timestamp::Seconds(0.0));
+	let before = match (start, to) {
+		(Timestamp::Seconds(start), Some(Timestamp::Seconds(to))) => to <= start,
+		(Timestamp::Samples(start), Some(Timestamp::Samples(to))) => to <= start,
+		_ => false,
+	};
+	if before {
+		return Err(Error::invalid_data("-to must be after -ss"));
+	}
+	Ok(Some(Trim::new(start, end)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn range(seek: Option<&str>, duration: Option<&str>, to: Option<&str>) -> Result<Option<Trim>> {
+		let owned = |value: Option<&str>| value.map(str::to_string);
+		parse_range(owned(seek), owned(duration), owned(to))
+	}
+
+	#[test]
+	fn test_range_options() {
+		assert!(range(None, None, None).unwrap().is_none());
+
+		let cut = range(Some("00:00:01.5"), Some("2"), None).unwrap().unwrap();
+		assert_eq!(cut.range(1000).unwrap(), (1500, Some(3500)));
+		let cut = range(Some("1"), None, Some("4000samples")).unwrap().unwrap();
+		assert_eq!(cut.range(1000).unwrap(), (1000, Some(4000)));
+		let cut = range(None, Some("500samples"), None).unwrap().unwrap();
+		assert_eq!(cut.range(1000).unwrap(), (0, Some(500)));
+
+		assert!(range(Some("1"), Some("2"), Some("3")).is_err());
+		assert!(range(Some("5"), None, Some("4")).is_err());
+	}
+}
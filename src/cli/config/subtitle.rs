@@ -1,10 +1,10 @@
 use super::track::parse_track_id;
 use crate::{
-	cli::config::parse_flags,
+	cli::config::{overlay, parse_flags},
 	io::{Error, Result},
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct SubtitleConfig {
 	pub track: Option<usize>,
 	pub language: Option<String>,
@@ -20,6 +20,29 @@ pub struct SubtitleConfig {
 }
 
 impl SubtitleConfig {
+	// blocks without a track apply to every track, then the ones naming `track` override them
+	pub fn for_track(configs: &[Self], track: usize) -> Self {
+		let mut config = Self { track: Some(track), ..Self::default() };
+		let general = configs.iter().filter(|block| block.track.is_none());
+		for block in general.chain(configs.iter().filter(|block| block.track == Some(track))) {
+			config.overlay(block);
+		}
+		config
+	}
+
+	fn overlay(&mut self, other: &Self) {
+		overlay(&mut self.language, &other.language);
+		overlay(&mut self.codec, &other.codec);
+		overlay(&mut self.default, &other.default);
+		overlay(&mut self.shift, &other.shift);
+		overlay(&mut self.font_size, &other.font_size);
+		overlay(&mut self.color, &other.color);
+		overlay(&mut self.position, &other.position);
+		overlay(&mut self.fps, &other.fps);
+		overlay(&mut self.encoding, &other.encoding);
+		overlay(&mut self.translate, &other.translate);
+	}
+
 	pub fn set_track(&mut self, track: Option<usize>) {
 		self.track = track;
 	}
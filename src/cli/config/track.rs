@@ -11,6 +11,10 @@ pub enum Track {
 
 impl Track {
 	pub fn resolve(&self, max_track: usize) -> Result<Vec<usize>> {
+		if max_track == 0 {
+			return Err(Error::invalid_data("no streams"));
+		}
+
 		if let Track::One(idx) = self {
 			if *idx >= max_track {
 				let msg = format!("track {} out of bounds (max {})", idx, max_track - 1);
@@ -19,10 +23,6 @@ impl Track {
 			return Ok(vec![*idx]);
 		}
 
-		if max_track == 0 {
-			return Err(Error::invalid_data("no streams"));
-		}
-
 		Ok((0..max_track).collect())
 	}
 
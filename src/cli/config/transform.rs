@@ -4,6 +4,8 @@ use crate::{cli::config::parse_flags, io::Result};
 #[derive(Debug, Default)]
 pub struct TransformConfig {
 	pub track: Option<usize>,
+	pub volume: Option<String>,
+	pub clip: Option<String>,
 	pub normalize: Option<String>,
 	pub trim: Option<String>,
 	pub fade: Option<String>,
@@ -19,6 +21,8 @@ pub fn parse_transform(tokens: Vec<String>) -> Result<TransformConfig> {
 
 	Ok(TransformConfig {
 		track,
+		volume: map.get("volume").cloned(),
+		clip: map.get("clip").cloned(),
 		normalize: map.get("normalize").cloned(),
 		trim: map.get("trim").cloned(),
 		fade: map.get("fade").cloned(),
@@ -1,7 +1,10 @@
 use super::track::parse_track_id;
-use crate::{cli::config::parse_flags, io::Result};
+use crate::{
+	cli::config::{overlay, parse_flags},
+	io::Result,
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct VideoConfig {
 	pub track: Option<usize>,
 	pub codec: Option<String>,
@@ -16,6 +19,31 @@ pub struct VideoConfig {
 	pub contrast: Option<String>,
 }
 
+impl VideoConfig {
+	// blocks without a track apply to every track, then the ones naming `track` override them
+	pub fn for_track(configs: &[Self], track: usize) -> Self {
+		let mut config = Self { track: Some(track), ..Self::default() };
+		let general = configs.iter().filter(|block| block.track.is_none());
+		for block in general.chain(configs.iter().filter(|block| block.track == Some(track))) {
+			config.overlay(block);
+		}
+		config
+	}
+
+	fn overlay(&mut self, other: &Self) {
+		overlay(&mut self.codec, &other.codec);
+		overlay(&mut self.scale, &other.scale);
+		overlay(&mut self.width, &other.width);
+		overlay(&mut self.height, &other.height);
+		overlay(&mut self.fps, &other.fps);
+		overlay(&mut self.bitrate, &other.bitrate);
+		overlay(&mut self.aspect_ratio, &other.aspect_ratio);
+		overlay(&mut self.rotate, &other.rotate);
+		overlay(&mut self.brightness, &other.brightness);
+		overlay(&mut self.contrast, &other.contrast);
+	}
+}
+
 pub fn parse_video(tokens: Vec<String>) -> Result<VideoConfig> {
 	let map = parse_flags(tokens, false);
 	let track = parse_track_id(&map)?;
@@ -1,53 +1,60 @@
 use crate::cli::{config, pipeline, utils};
-use crate::container;
 use crate::core::compatible;
+use crate::core::registry::Registry;
 use crate::{cli, io};
 
 pub fn execute(cli: cli::Cli) -> io::Result<()> {
+	let registry = Registry::default();
 	let mut pipe = pipeline::Pipeline::new(&cli.input, &cli.output);
 
-	let audio = config::parse_audio(cli.audio)?;
-	let video = config::parse_video(cli.video)?;
-	let subtitle = config::parse_subtitle(cli.subtitle)?;
+	let audio = cli.audio.into_iter().map(config::parse_audio).collect::<io::Result<Vec<_>>>()?;
+	let video = cli.video.into_iter().map(config::parse_video).collect::<io::Result<Vec<_>>>()?;
+	let subtitle =
+		cli.subtitle.into_iter().map(config::parse_subtitle).collect::<io::Result<Vec<_>>>()?;
+	pipe.with_input_audio(config::parse_audio(cli.input_audio)?);
 	let transform = config::parse_transform(cli.apply)?;
 	pipe.with_transform(transform);
-
-	let input_ext = utils::get_extension(&cli.input)?;
-	let output_ext = utils::get_extension(&cli.output)?;
-
-	let compat = compatible::Compatible::new();
+	pipe.with_maps(config::parse_maps(cli.map)?);
+	pipe.with_range(config::parse_range(cli.seek, cli.duration, cli.to)?);
+	pipe.with_metadata(config::parse_metadata(cli.metadata, cli.metadata_clear)?);
+
+	// "-" reads stdin or writes stdout, `--format` and `--output-format` name what is there
+	let input_ext = match (cli.format, cli.input.as_str()) {
+		(Some(format), _) => format.to_lowercase(),
+		(None, "-") => {
+			let (format, prefix) = utils::probe_stdin(&registry.prober())?;
+			pipe.with_input_prefix(prefix);
+			format
+		}
+		(None, path) => utils::detect_format(path, &registry.prober())?,
+	};
+	let output_ext = match (cli.output_format, cli.output.as_str()) {
+		(Some(format), _) => format.to_lowercase(),
+		(None, "-") => input_ext.clone(),
+		(None, path) => utils::get_extension(path)?,
+	};
+
+	let compat = compatible::Compatible::from_registry(&registry);
 	compat.assert_container_supported(&input_ext)?;
 	compat.assert_container_supported(&output_ext)?;
 
-	if let Some(codec) = &audio.codec {
-		compat.assert_audio_supported(&input_ext, codec)?;
-		pipe.with_audio(audio);
+	// codecs are checked against the container they end up in
+	for codec in audio.iter().filter_map(|config| config.codec.as_ref()) {
+		compat.assert_audio_supported(&output_ext, codec)?;
 	}
+	pipe.with_input_format(&input_ext);
+	pipe.with_output_format(&output_ext);
+	pipe.with_audio(audio);
 
-	if let Some(codec) = &video.codec {
-		compat.assert_video_supported(&input_ext, codec)?;
-		pipe.with_video(video);
+	for codec in video.iter().filter_map(|config| config.codec.as_ref()) {
+		compat.assert_video_supported(&output_ext, codec)?;
 	}
+	pipe.with_video(video);
 
-	if let Some(codec) = &subtitle.codec {
-		compat.assert_subtitle_supported(&input_ext, codec)?;
-		pipe.with_subtitle(subtitle);
+	for codec in subtitle.iter().filter_map(|config| config.codec.as_ref()) {
+		compat.assert_subtitle_supported(&output_ext, codec)?;
 	}
+	pipe.with_subtitle(subtitle);
 
-	// Route based on output format first for clarity
-	match output_ext.as_str() {
-		container::WAV => pipeline::wav::run(pipe),
-		container::RAW | container::PCM => pipeline::raw::run(pipe),
-		_ => {
-			// Fall back to input-based routing
-			match input_ext.as_str() {
-				container::WAV => pipeline::wav::run(pipe),
-				container::RAW | container::PCM => pipeline::raw::run(pipe),
-				// container::AAC => pipeline::aac::run(pipe),
-				// container::MKV => pipeline::mkv::run(pipe),
-				container::MOV => pipeline::webm::run(pipe),
-				_ => Err(io::Error::invalid_data(format!("unsupported '{}' format", input_ext))),
-			}
-		}
-	}
+	pipeline::generic::run(pipe, &registry)
 }
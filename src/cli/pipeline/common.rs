@@ -1,13 +1,25 @@
 use crate::cli::config;
+use crate::transform::Trim;
 
 #[derive(Debug, Default)]
 pub struct Pipeline {
 	pub input: String,
 	pub output: String,
-	pub audio: config::AudioConfig,
-	pub video: config::VideoConfig,
-	pub subtitle: config::SubtitleConfig,
+	// detected from the content when possible, otherwise the extension
+	pub input_format: String,
+	// bytes probing took from stdin, the demuxer reads them first
+	pub input_prefix: Vec<u8>,
+	// describes raw input, which has no header to read it from
+	pub input_audio: config::AudioConfig,
+	pub output_format: String,
+	pub audio: Vec<config::AudioConfig>,
+	pub video: Vec<config::VideoConfig>,
+	pub subtitle: Vec<config::SubtitleConfig>,
 	pub transform: config::TransformConfig,
+	pub maps: Vec<config::StreamMap>,
+	// part of the input to process, from `-ss`, `-t` and `-to`
+	pub range: Option<Trim>,
+	pub metadata: config::MetadataConfig,
 }
 
 impl Pipeline {
@@ -15,19 +27,60 @@ impl Pipeline {
 		Self { input: input.to_string(), output: output.to_string(), ..Default::default() }
 	}
 
-	pub fn with_audio(&mut self, audio: config::AudioConfig) {
+	pub fn with_input_format(&mut self, format: &str) {
+		self.input_format = format.to_string();
+	}
+
+	pub fn with_input_prefix(&mut self, prefix: Vec<u8>) {
+		self.input_prefix = prefix;
+	}
+
+	pub fn with_input_audio(&mut self, input_audio: config::AudioConfig) {
+		self.input_audio = input_audio;
+	}
+
+	pub fn with_output_format(&mut self, format: &str) {
+		self.output_format = format.to_string();
+	}
+
+	pub fn with_audio(&mut self, audio: Vec<config::AudioConfig>) {
 		self.audio = audio;
 	}
 
-	pub fn with_video(&mut self, video: config::VideoConfig) {
+	pub fn with_video(&mut self, video: Vec<config::VideoConfig>) {
 		self.video = video;
 	}
 
-	pub fn with_subtitle(&mut self, subtitle: config::SubtitleConfig) {
+	pub fn with_subtitle(&mut self, subtitle: Vec<config::SubtitleConfig>) {
 		self.subtitle = subtitle;
 	}
 
 	pub fn with_transform(&mut self, transform: config::TransformConfig) {
 		self.transform = transform;
 	}
+
+	pub fn with_maps(&mut self, maps: Vec<config::StreamMap>) {
+		self.maps = maps;
+	}
+
+	pub fn with_range(&mut self, range: Option<Trim>) {
+		self.range = range;
+	}
+
+	pub fn with_metadata(&mut self, metadata: config::MetadataConfig) {
+		self.metadata = metadata;
+	}
+
+	// `track` counts streams of the same kind in the input
+	pub fn audio_for(&self, track: usize) -> config::AudioConfig {
+		config::AudioConfig::for_track(&self.audio, track)
+	}
+
+	pub fn video_for(&self, track: usize) -> config::VideoConfig {
+		config::VideoConfig::for_track(&self.video, track)
+	}
+
+	pub fn subtitle_for(&self, track: usize) -> config::SubtitleConfig {
+		config::SubtitleConfig::for_track(&self.subtitle, track)
+	}
 }
@@ -0,0 +1,427 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::common::Pipeline;
+use crate::cli::config::{self, AudioConfig, Track};
+use crate::cli::transcoder::{media, transforms};
+use crate::container::interleave::Interleaver;
+use crate::core::packet::Packet;
+use crate::core::parameters::AudioParameters;
+use crate::core::registry::{DemuxerOptions, Registry};
+use crate::core::stream::{Stream, StreamKind};
+use crate::core::time::Time;
+use crate::core::{Demuxer, Muxer};
+use crate::io::stdio::{StdioSink, StdioSource};
+use crate::io::{DEFAULT_BUFFER_SIZE, Error, MediaRead, Result};
+use crate::transform::TransformChain;
+
+// one selected input stream on its way to the muxer, without a transcoder its packets are copied
+struct Route {
+	input_index: usize,
+	output_index: usize,
+	transcoder: Option<media::Transcoder>,
+}
+
+// a copy of stdin on disk, for passes that read the input more than once, removed on drop
+struct Spool {
+	path: PathBuf,
+}
+
+impl Spool {
+	fn create(mut source: impl MediaRead) -> Result<Self> {
+		let name = format!("ffmpreg-stdin-{}.tmp", std::process::id());
+		let spool = Self { path: std::env::temp_dir().join(name) };
+		let mut file = fs::File::create(&spool.path)?;
+		let mut buffer = vec![0; DEFAULT_BUFFER_SIZE];
+		loop {
+			match source.read(&mut buffer)? {
+				0 => break,
+				read => file.write_all(&buffer[..read])?,
+			}
+		}
+		Ok(spool)
+	}
+
+	fn path(&self) -> Result<&str> {
+		self.path.to_str().ok_or_else(|| Error::invalid_data("temp dir path is not utf-8"))
+	}
+}
+
+impl Drop for Spool {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+// every format goes through here, the registry supplies the demuxer, codecs and muxer
+pub fn run(pipeline: Pipeline, registry: &Registry) -> Result<()> {
+	// stdin can only be read once, normalize analyses the input first and reads it from a copy
+	let spool = match (pipeline.input.as_str(), &pipeline.transform.normalize) {
+		("-", Some(_)) => Some(Spool::create(StdioSource::stdin(pipeline.input_prefix.clone()))?),
+		_ => None,
+	};
+	let input_path = match &spool {
+		Some(spool) => spool.path()?,
+		None => pipeline.input.as_str(),
+	};
+
+	let source = match input_path {
+		"-" => StdioSource::stdin(pipeline.input_prefix.clone()),
+		path => StdioSource::open(path)?,
+	};
+	let input = input_options(&pipeline)?;
+	let demuxer = registry.open_demuxer(&pipeline.input_format, source, &input)?;
+	let mut metadata = pipeline.metadata.apply(demuxer.metadata().cloned());
+	let input_streams = demuxer.streams().all().to_vec();
+	// stdin can only be read once, the demuxer that read the header goes on with the data
+	let mut piped = (input_path == "-").then_some(demuxer);
+
+	if input_streams.is_empty() {
+		return Err(Error::invalid_data("input has no streams"));
+	}
+	check_tracks(&pipeline, &input_streams)?;
+	let selected = config::select_streams(&pipeline.maps, &input_streams)?;
+
+	// the demuxer skips what the range and the trim cut off, following the first audio stream,
+	// frames that still get through are dropped by the range at the head of each chain
+	let trim_rate =
+		input_streams.iter().find_map(|stream| stream.audio_parameters()).map(|a| a.sample_rate);
+	let sample_range = match trim_rate {
+		Some(rate) => sample_range(&pipeline, rate)?,
+		None => None,
+	};
+	let options = DemuxerOptions { sample_range, ..input };
+	let open_demuxer = || match input_path {
+		"-" => Err(Error::invalid_data("stdin can only be read once")),
+		path => registry.open_demuxer(&pipeline.input_format, StdioSource::open(path)?, &options),
+	};
+
+	let mut routes = Vec::with_capacity(selected.len());
+	let mut output_streams = Vec::with_capacity(selected.len());
+	for input in selected.iter().map(|&index| &input_streams[index]) {
+		let track = kind_track(&input_streams, input);
+		let mut output = output_stream(&pipeline, input, track)?;
+		output.index = output_streams.len();
+
+		let transformed = pipeline.transform.track.is_none_or(|only| only == track);
+		let transforms = match input.kind {
+			StreamKind::Audio if transformed => {
+				let audio = pipeline.audio_for(track);
+				Some(build_audio_transforms(&pipeline, &audio, registry, input, &open_demuxer)?)
+			}
+			StreamKind::Audio => Some(input_range(&pipeline)),
+			// video and subtitles have no transforms, keeping their codec they are copied
+			_ if output.codec == input.codec => None,
+			_ => Some(TransformChain::new()),
+		};
+		let transcoder = match transforms {
+			Some(transforms) => {
+				let decoder = registry.create_decoder(input)?;
+				let encoder = registry.create_encoder(&output)?;
+				Some(media::Transcoder::new(decoder, encoder).with_transforms(transforms))
+			}
+			None => None,
+		};
+
+		routes.push(Route { input_index: input.index, output_index: output.index, transcoder });
+		output_streams.push(output);
+	}
+
+	// sample positions in the tags follow the cut and the rate of the first audio output
+	let output_rate =
+		output_streams.iter().find_map(|stream| stream.audio_parameters()).map(|a| a.sample_rate);
+	if let (Some(metadata), Some(from), Some(to)) = (&mut metadata, trim_rate, output_rate) {
+		let (start, end) = sample_range.unwrap_or((0, None));
+		metadata.retime(start, end, from, to);
+	}
+
+	let sink = StdioSink::create(&pipeline.output)?;
+	let mut muxer = registry.create_muxer(&pipeline.output_format, sink, &output_streams)?;
+	if let Some(metadata) = metadata {
+		muxer.set_metadata(metadata);
+	}
+
+	let mut demuxer = match piped.take() {
+		Some(demuxer) => demuxer,
+		None => open_demuxer()?,
+	};
+	let mut interleaver = Interleaver::new(routes.len());
+	transcode(demuxer.as_mut(), &mut routes, &mut interleaver, muxer.as_mut())?;
+	muxer.finalize()
+}
+
+fn transcode(
+	demuxer: &mut dyn Demuxer,
+	routes: &mut [Route],
+	interleaver: &mut Interleaver,
+	muxer: &mut dyn Muxer,
+) -> Result<()> {
+	while let Some(packet) = demuxer.read_packet()? {
+		// a stream can be mapped more than once, or not at all and then it is dropped here
+		for route in routes.iter_mut().filter(|route| route.input_index == packet.stream_index) {
+			let packets = match route.transcoder.as_mut() {
+				Some(transcoder) => transcoder.transcode(packet.clone())?,
+				None => vec![packet.clone()],
+			};
+			write(interleaver, muxer, route.output_index, packets)?;
+		}
+	}
+
+	for route in routes.iter_mut() {
+		while let Some(transcoder) = route.transcoder.as_mut() {
+			let packets = transcoder.flush()?;
+			if packets.is_empty() {
+				break;
+			}
+			write(interleaver, muxer, route.output_index, packets)?;
+		}
+		interleaver.finish(route.output_index);
+	}
+
+	while let Some(packet) = interleaver.pop() {
+		muxer.write(packet)?;
+	}
+	Ok(())
+}
+
+// encoders know nothing of the output layout, packets are renumbered before they are queued
+fn write(
+	interleaver: &mut Interleaver,
+	muxer: &mut dyn Muxer,
+	output_index: usize,
+	packets: Vec<Packet>,
+) -> Result<()> {
+	for mut packet in packets {
+		packet.stream_index = output_index;
+		interleaver.push(packet);
+	}
+	while let Some(packet) = interleaver.pop() {
+		muxer.write(packet)?;
+	}
+	Ok(())
+}
+
+// `--input-audio` as the demuxer takes it
+fn input_options(pipeline: &Pipeline) -> Result<DemuxerOptions> {
+	let audio = &pipeline.input_audio;
+	Ok(DemuxerOptions {
+		sample_range: None,
+		codec: audio.codec.clone(),
+		sample_rate: audio.parse_sample_rate()?,
+		layout: audio.parse_channels()?,
+	})
+}
+
+// `-ss`, `-t` and `-to` cut the decoded frames and rebase them to zero
+fn input_range(pipeline: &Pipeline) -> TransformChain {
+	let mut transforms = TransformChain::new();
+	if let Some(range) = pipeline.range {
+		transforms.push(Box::new(range));
+	}
+	transforms
+}
+
+// the audio chain for one stream, normalize gets its own analysis pass over that stream
+fn build_audio_transforms(
+	pipeline: &Pipeline,
+	audio: &AudioConfig,
+	registry: &Registry,
+	input: &Stream,
+	open_demuxer: &dyn Fn() -> Result<Box<dyn Demuxer>>,
+) -> Result<TransformChain> {
+	let sample_rate = input.audio_parameters().map_or(input.time.den, |audio| audio.sample_rate);
+	let duration = input.duration.map(|duration| duration as u64);
+	let range = sample_range(pipeline, sample_rate)?;
+
+	let config = &pipeline.transform;
+	let mut transforms = input_range(pipeline).with(Box::new(transforms::build(audio, config)?));
+	if let Some(mut normalize) = transforms::build_normalize(config)? {
+		let mut demuxer = open_demuxer()?;
+		let mut decoder = registry.create_decoder(input)?;
+		let mut measured = input_range(pipeline).with(Box::new(transforms::build(audio, config)?));
+		let (demuxer, decoder) = (demuxer.as_mut(), decoder.as_mut());
+		transforms::analyze(demuxer, input.index, decoder, &mut measured, &mut normalize)?;
+		transforms.push(Box::new(normalize));
+	}
+	if let Some(fade) = transforms::build_fade(config, sample_rate, duration, range)? {
+		transforms.push(Box::new(fade));
+	}
+	Ok(transforms)
+}
+
+// input samples to deliver, a trim counts from the start of the input range
+fn sample_range(pipeline: &Pipeline, sample_rate: u32) -> Result<Option<(u64, Option<u64>)>> {
+	let range = pipeline.range.map(|range| range.range(sample_rate)).transpose()?;
+	let trim = transforms::trim_range(&pipeline.transform, sample_rate)?;
+	Ok(match (range, trim) {
+		(Some((start, end)), Some((trim_start, trim_end))) => {
+			let trim_end = trim_end.map(|trim_end| start + trim_end);
+			let end = match (end, trim_end) {
+				(Some(end), Some(trim_end)) => Some(end.min(trim_end)),
+				(end, trim_end) => end.or(trim_end),
+			};
+			Some((start + trim_start, end))
+		}
+		(range, trim) => range.or(trim),
+	})
+}
+
+// position of `stream` among the input streams of its kind, what `track=` refers to
+fn kind_track(streams: &[Stream], stream: &Stream) -> usize {
+	streams.iter().filter(|other| other.kind == stream.kind && other.index < stream.index).count()
+}
+
+// option blocks naming a track the input does not have are an error, not silently ignored
+fn check_tracks(pipeline: &Pipeline, streams: &[Stream]) -> Result<()> {
+	let count = |kind| streams.iter().filter(|stream| stream.kind == kind).count();
+	let check = |track: Option<usize>, kind| match track {
+		Some(track) => Track::One(track).resolve(count(kind)).map(|_| ()),
+		None => Ok(()),
+	};
+
+	pipeline.audio.iter().try_for_each(|config| check(config.track, StreamKind::Audio))?;
+	pipeline.video.iter().try_for_each(|config| check(config.track, StreamKind::Video))?;
+	pipeline.subtitle.iter().try_for_each(|config| check(config.track, StreamKind::Subtitle))?;
+	check(pipeline.transform.track, StreamKind::Audio)
+}
+
+// the input stream as the options for its kind and track and the transforms leave it
+fn output_stream(pipeline: &Pipeline, input: &Stream, track: usize) -> Result<Stream> {
+	let mut stream = input.clone();
+	stream.duration = None;
+
+	let codec = match stream.kind {
+		StreamKind::Audio => {
+			let config = pipeline.audio_for(track);
+			let layout = config.parse_channels()?;
+			let sample_rate = config.parse_sample_rate()?;
+			if let Some(sample_rate) = sample_rate {
+				stream.time = Time::new(1, sample_rate);
+			}
+			if let Some(audio) = stream.audio_parameters_mut() {
+				audio.layout = layout.unwrap_or(audio.layout);
+				audio.sample_rate = sample_rate.unwrap_or(audio.sample_rate);
+			}
+			config.codec
+		}
+		StreamKind::Video => pipeline.video_for(track).codec,
+		StreamKind::Subtitle => pipeline.subtitle_for(track).codec,
+	};
+
+	// a different codec brings its own sample size, the encoder decides it
+	if let Some(codec) = codec.filter(|codec| *codec != stream.codec) {
+		stream.codec = codec;
+		if let Some(audio) = stream.audio_parameters_mut() {
+			*audio = AudioParameters::new(audio.sample_rate, audio.layout, 0);
+		}
+	}
+	Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::codecs::audio::PCM_S16LE;
+	use crate::codecs::audio::pcm::{PcmDecoder, PcmEncoder};
+	use crate::core::frame::ChannelLayout;
+	use crate::core::parameters::CodecParameters;
+	use crate::core::stream::Streams;
+
+	struct Demux(Streams, Vec<Packet>);
+
+	impl Demuxer for Demux {
+		fn streams(&self) -> &Streams {
+			&self.0
+		}
+		fn read_packet(&mut self) -> Result<Option<Packet>> {
+			Ok((!self.1.is_empty()).then(|| self.1.remove(0)))
+		}
+	}
+
+	struct Mux(Streams, Vec<(usize, f64)>);
+
+	impl Muxer for Mux {
+		fn streams(&self) -> &Streams {
+			&self.0
+		}
+		fn write(&mut self, packet: Packet) -> Result<()> {
+			self.1.push((packet.stream_index, packet.time.to_seconds(packet.dts)));
+			Ok(())
+		}
+		fn finalize(&mut self) -> Result<()> {
+			Ok(())
+		}
+	}
+
+	fn route(input_index: usize, output_index: usize, rate: u32) -> Route {
+		let decoder = PcmDecoder::new(rate, 1, 2);
+		let transcoder = media::Transcoder::new(Box::new(decoder), Box::new(PcmEncoder::new(rate)));
+		Route { input_index, output_index, transcoder: Some(transcoder) }
+	}
+
+	#[test]
+	fn test_routes_selected_streams_in_dts_order() {
+		let stream = |index, rate| {
+			let audio = AudioParameters::new(rate, ChannelLayout::MONO, 16);
+			Stream::new(0, index, StreamKind::Audio, PCM_S16LE.into(), Time::new(1, rate))
+				.with_parameters(CodecParameters::Audio(audio))
+		};
+		// the middle stream is not selected
+		let streams = Streams::new(vec![stream(0, 8000), stream(1, 8000), stream(2, 16000)]);
+
+		// the first stream is read well ahead of the last one
+		let packet = |index, rate, pts| {
+			Packet::new(vec![0; 1600], index, Time::new(1, rate)).with_pts(pts).with_dts(pts)
+		};
+		let packets = vec![
+			packet(0, 8000, 0),
+			packet(0, 8000, 800),
+			packet(1, 8000, 0),
+			packet(2, 16000, 0),
+			packet(2, 16000, 800),
+		];
+
+		let mut demuxer = Demux(streams.clone(), packets);
+		let mut muxer = Mux(Streams::new_empty(), Vec::new());
+		let mut routes = vec![route(0, 0, 8000), route(2, 1, 16000)];
+		let mut interleaver = Interleaver::new(2);
+		transcode(&mut demuxer, &mut routes, &mut interleaver, &mut muxer).unwrap();
+
+		assert_eq!(muxer.1, vec![(0, 0.0), (1, 0.0), (1, 0.05), (0, 0.1)]);
+	}
+
+	#[test]
+	fn test_spool_copies_the_input() {
+		let data: Vec<u8> = (0..100_000).map(|n| n as u8).collect();
+		let spool = Spool::create(crate::io::Cursor::new(data.clone())).unwrap();
+		let path = spool.path.clone();
+		assert_eq!(fs::read(&path).unwrap(), data);
+		drop(spool);
+		assert!(!path.exists());
+	}
+
+	#[test]
+	fn test_copies_streams_without_a_transcoder() {
+		let audio = AudioParameters::new(8000, ChannelLayout::MONO, 16);
+		let streams = Streams::new(vec![
+			Stream::new(0, 0, StreamKind::Audio, PCM_S16LE.into(), Time::new(1, 8000))
+				.with_parameters(CodecParameters::Audio(audio)),
+			Stream::new(1, 1, StreamKind::Video, "h264".into(), Time::new(1, 25)),
+		]);
+
+		let audio = |pts| Packet::new(vec![0; 1600], 0, Time::new(1, 8000)).with_pts(pts).with_dts(pts);
+		let video = |pts| Packet::new(vec![1, 2, 3], 1, Time::new(1, 25)).with_pts(pts).with_dts(pts);
+		let packets = vec![audio(0), video(0), video(1), video(2), audio(800), video(3)];
+
+		let mut demuxer = Demux(streams.clone(), packets);
+		let mut muxer = Mux(Streams::new_empty(), Vec::new());
+		let copy = Route { input_index: 1, output_index: 1, transcoder: None };
+		let mut routes = vec![route(0, 0, 8000), copy];
+		let mut interleaver = Interleaver::new(2);
+		transcode(&mut demuxer, &mut routes, &mut interleaver, &mut muxer).unwrap();
+
+		let expected = [(0, 0.0), (1, 0.0), (1, 0.04), (1, 0.08), (0, 0.1), (1, 0.12)];
+		assert_eq!(muxer.1, expected);
+	}
+}
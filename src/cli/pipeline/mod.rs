@@ -1,7 +1,3 @@
-pub mod aac;
 mod common;
-// pub mod mkv;
-pub mod raw;
-pub mod wav;
-pub mod webm;
+pub mod generic;
 pub use common::Pipeline;
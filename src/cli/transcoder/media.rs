@@ -1,40 +1,84 @@
+use crate::core::frame::Frame;
 use crate::core::packet::Packet;
-use crate::core::{Decoder, Encoder};
+use crate::core::{Decoder, Encoder, Transform};
 use crate::io;
+use crate::transform::TransformChain;
 
 pub struct Transcoder {
 	pub decoder: Box<dyn Decoder>,
 	pub encoder: Box<dyn Encoder>,
+	pub transforms: TransformChain,
+	decoder_drained: bool,
+	encoder_drained: bool,
 }
 
 impl Transcoder {
 	pub fn new(decoder: Box<dyn Decoder>, encoder: Box<dyn Encoder>) -> Self {
-		Self { decoder, encoder }
+		Self {
+			decoder,
+			encoder,
+			transforms: TransformChain::new(),
+			decoder_drained: false,
+			encoder_drained: false,
+		}
+	}
+
+	pub fn with_transforms(mut self, transforms: TransformChain) -> Self {
+		self.transforms = transforms;
+		self
 	}
 
 	pub fn transcode(&mut self, packet: Packet) -> io::Result<Vec<Packet>> {
-		let mut packets = Vec::new();
-		if let Some(frame) = self.decoder.decode(packet)? {
-			if let Some(encoded_packet) = self.encoder.encode(frame)? {
-				packets.push(encoded_packet);
-			}
-		}
-		Ok(packets)
+		self.decoder.send_packet(Some(packet))?;
+		self.process_decoded()
 	}
 
+	// drains the decoder, the transforms and then the encoder, called until it returns no packets
 	pub fn flush(&mut self) -> io::Result<Vec<Packet>> {
 		let mut packets = Vec::new();
 
-		while let Some(frame) = self.decoder.flush()? {
-			if let Some(encoded_packet) = self.encoder.encode(frame)? {
-				packets.push(encoded_packet);
+		if !self.decoder_drained {
+			self.decoder.send_packet(None)?;
+			packets.extend(self.process_decoded()?);
+			self.decoder_drained = true;
+		}
+
+		// buffering transforms may release a lot at the end, hand it out in pieces
+		loop {
+			let frames = self.transforms.flush()?;
+			if frames.is_empty() {
+				break;
+			}
+			packets.extend(self.encode(frames)?);
+			if !packets.is_empty() {
+				return Ok(packets);
 			}
 		}
 
-		while let Some(packet) = self.encoder.flush()? {
-			packets.push(packet);
+		if !self.encoder_drained {
+			self.encoder.send_frame(None)?;
+			packets.extend(self.encoder.receive_packets()?);
+			self.encoder_drained = true;
 		}
 
 		Ok(packets)
 	}
+
+	fn process_decoded(&mut self) -> io::Result<Vec<Packet>> {
+		let mut packets = Vec::new();
+		for frame in self.decoder.receive_frames()? {
+			let frames = self.transforms.apply(frame)?;
+			packets.extend(self.encode(frames)?);
+		}
+		Ok(packets)
+	}
+
+	fn encode(&mut self, frames: Vec<Frame>) -> io::Result<Vec<Packet>> {
+		let mut packets = Vec::new();
+		for frame in frames {
+			self.encoder.send_frame(Some(frame))?;
+			packets.extend(self.encoder.receive_packets()?);
+		}
+		Ok(packets)
+	}
 }
@@ -0,0 +1,136 @@
+use crate::cli::config::{AudioConfig, TransformConfig};
+use crate::core::{Decoder, Demuxer, Transform};
+use crate::io::{Error, Result};
+use crate::transform::remix::parse_matrix;
+use crate::transform::{
+	ClipMode, Fade, Normalize, Remix, Resample, ResampleQuality, Reverse, Speed, TransformChain,
+	Trim, Volume,
+};
+
+// every transform except normalize, which needs an analysis pass first
+pub fn build(audio: &AudioConfig, config: &TransformConfig) -> Result<TransformChain> {
+	let mut transforms = TransformChain::new();
+
+	if let Some(trim) = &config.trim {
+		transforms.push(Box::new(Trim::parse(trim)?));
+	}
+
+	if let Some(layout) = audio.parse_channels()? {
+		let mut remix = Remix::new(layout);
+		if let Some(matrix) = &audio.matrix {
+			remix = remix.with_matrix(parse_matrix(matrix)?);
+		}
+		transforms.push(Box::new(remix));
+	}
+
+	if let Some(sample_rate) = audio.parse_sample_rate()? {
+		let mut resample = Resample::new(sample_rate);
+		if let Some(quality) = &audio.resampler {
+			resample = resample.with_quality(ResampleQuality::parse(quality)?);
+		}
+		transforms.push(Box::new(resample));
+	}
+
+	if config.reverse.as_deref().is_some_and(|reverse| reverse != "false") {
+		transforms.push(Box::new(Reverse::new()));
+	}
+
+	if let Some(speed) = &config.speed {
+		transforms.push(Box::new(Speed::parse(speed)?));
+	}
+
+	if let Some(volume) = &config.volume {
+		let mut volume = Volume::parse(volume)?;
+		if let Some(clip) = &config.clip {
+			volume = volume.with_clip(ClipMode::parse(clip)?);
+		}
+		transforms.push(Box::new(volume));
+	}
+
+	Ok(transforms)
+}
+
+// sample range the demuxer has to deliver, so data outside a trim is never decoded
+pub fn trim_range(
+	config: &TransformConfig,
+	sample_rate: u32,
+) -> Result<Option<(u64, Option<u64>)>> {
+	config.trim.as_deref().map(|trim| Trim::parse(trim)?.range(sample_rate)).transpose()
+}
+
+// `input_samples` is the length of the decoded input, cut to `range` and sped up here as the
+// chain will
+pub fn build_fade(
+	config: &TransformConfig,
+	sample_rate: u32,
+	input_samples: Option<u64>,
+	range: Option<(u64, Option<u64>)>,
+) -> Result<Option<Fade>> {
+	let Some(fade) = &config.fade else {
+		return Ok(None);
+	};
+	let fade = Fade::parse(fade)?;
+
+	let samples = match range {
+		Some((start, Some(end))) => {
+			Some(input_samples.map_or(end, |n| n.min(end)).saturating_sub(start))
+		}
+		Some((start, None)) => input_samples.map(|n| n.saturating_sub(start)),
+		None => input_samples,
+	};
+
+	let speed = config.speed.as_deref().map(Speed::parse).transpose()?;
+	let factor = speed.map_or(1.0, |speed| speed.factor());
+
+	match samples {
+		Some(samples) => {
+			let seconds = samples as f64 / sample_rate as f64 / factor;
+			Ok(Some(fade.with_total_duration(seconds)))
+		}
+		None if fade.needs_duration() => {
+			Err(Error::invalid_data("fade out needs an input with a known duration or a trim end"))
+		}
+		None => Ok(Some(fade)),
+	}
+}
+
+pub fn build_normalize(config: &TransformConfig) -> Result<Option<Normalize>> {
+	config.normalize.as_deref().map(Normalize::parse).transpose()
+}
+
+// first pass over one input stream: decode, run the chain that precedes normalize, and measure
+pub fn analyze(
+	demuxer: &mut dyn Demuxer,
+	stream_index: usize,
+	decoder: &mut dyn Decoder,
+	transforms: &mut TransformChain,
+	normalize: &mut Normalize,
+) -> Result<()> {
+	let mut measure = |frames: Vec<_>| frames.iter().try_for_each(|frame| normalize.analyze(frame));
+
+	while let Some(packet) = demuxer.read_packet()? {
+		if packet.stream_index != stream_index {
+			continue;
+		}
+		decoder.send_packet(Some(packet))?;
+		for frame in decoder.receive_frames()? {
+			measure(transforms.apply(frame)?)?;
+		}
+	}
+
+	decoder.send_packet(None)?;
+	for frame in decoder.receive_frames()? {
+		measure(transforms.apply(frame)?)?;
+	}
+
+	loop {
+		let frames = transforms.flush()?;
+		if frames.is_empty() {
+			break;
+		}
+		measure(frames)?;
+	}
+
+	normalize.finish_analysis();
+	Ok(())
+}
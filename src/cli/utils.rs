@@ -1,4 +1,6 @@
+use crate::container::probe::Prober;
 use crate::io;
+use crate::io::stdio::StdinAdapter;
 
 pub fn get_extension(path: &str) -> io::Result<String> {
 	std::path::Path::new(path)
@@ -7,3 +9,27 @@ pub fn get_extension(path: &str) -> io::Result<String> {
 		.map(|s| s.to_lowercase())
 		.ok_or_else(|| io::Error::invalid_data("no file extension"))
 }
+
+// probes the file content and only trusts the extension when probing is inconclusive
+pub fn detect_format(path: &str, prober: &Prober) -> io::Result<String> {
+	let mut file = io::File::open(path)?;
+	let (result, _) = prober.probe(&mut file)?;
+	match result {
+		Some(result) if result.is_confident() => Ok(result.format.to_string()),
+		result => get_extension(path).or_else(|error| match result {
+			Some(result) => Ok(result.format.to_string()),
+			None => {
+				Err(io::Error::invalid_data(format!("unable to detect format of '{}': {}", path, error)))
+			}
+		}),
+	}
+}
+
+// stdin has no extension to fall back on, the probed bytes are handed back to be read again
+pub fn probe_stdin(prober: &Prober) -> io::Result<(String, Vec<u8>)> {
+	let (result, data) = prober.probe(&mut StdinAdapter::new())?;
+	match result {
+		Some(result) => Ok((result.format.to_string(), data)),
+		None => Err(io::Error::invalid_data("unable to detect format of stdin, use --format")),
+	}
+}
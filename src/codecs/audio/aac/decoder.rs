@@ -1,5 +1,9 @@
-use crate::core::traits::Decoder;
-use crate::core::{AudioFormat, Frame, FrameAudio, Packet, Time};
+use std::collections::VecDeque;
+
+use crate::core::frame::{AudioFormat, Frame, FrameAudio};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::traits::{Decoder, Receive};
 use crate::io::Result as IoResult;
 use crate::io::{Error, ErrorKind};
 
@@ -10,7 +14,8 @@ pub struct AACDecoder {
 	last_header: Option<ADTSHeader>,
 	#[allow(dead_code)]
 	sample_buffer: Vec<u8>,
-	pending_frames: std::collections::VecDeque<Frame>,
+	pending_frames: VecDeque<Frame>,
+	finished: bool,
 }
 
 impl AACDecoder {
@@ -19,7 +24,8 @@ impl AACDecoder {
 			parser: ADTSParser::new(),
 			last_header: None,
 			sample_buffer: Vec::new(),
-			pending_frames: std::collections::VecDeque::new(),
+			pending_frames: VecDeque::new(),
+			finished: false,
 		}
 	}
 
@@ -34,6 +40,10 @@ impl AACDecoder {
 			return Err(Error::with_message(ErrorKind::InvalidData, "AAC profile must be 0-3"));
 		}
 
+		if (header.frame_length as usize) < header.header_size() {
+			return Err(Error::with_message(ErrorKind::InvalidData, "AAC frame shorter than its header"));
+		}
+
 		Ok(())
 	}
 
@@ -58,47 +68,39 @@ impl AACDecoder {
 
 		Ok(Frame::new_audio(audio, time, stream_index, 0).with_pts(packet_pts))
 	}
-}
-
-impl Decoder for AACDecoder {
-	fn decode(&mut self, packet: Packet) -> IoResult<Option<Frame>> {
-		if !packet.is_empty() {
-			self.parser.feed(&packet.data);
-		}
-
-		match self.parser.extract_frame()? {
-			Some((header, frame_data)) => {
-				Self::validate_header(&header)?;
-				self.last_header = Some(header);
 
-				let raw_aac = Self::extract_raw_aac_frame(&frame_data, &header);
-				let frame = Self::create_frame(raw_aac, &header, packet.pts, packet.stream_index)?;
+	// a packet can carry several adts frames, they all go out with its pts
+	fn drain(&mut self, pts: i64, stream_index: usize) -> IoResult<()> {
+		while let Some((header, frame_data)) = self.parser.extract_frame()? {
+			Self::validate_header(&header)?;
+			self.last_header = Some(header);
 
-				Ok(Some(frame))
-			}
-			None => Ok(None),
+			let raw_aac = Self::extract_raw_aac_frame(&frame_data, &header);
+			self.pending_frames.push_back(Self::create_frame(raw_aac, &header, pts, stream_index)?);
 		}
+		Ok(())
 	}
+}
 
-	fn flush(&mut self) -> IoResult<Option<Frame>> {
-		if self.pending_frames.is_empty() && self.parser.buffer_size() == 0 {
-			return Ok(None);
-		}
+impl Decoder for AACDecoder {
+	fn send_packet(&mut self, packet: Option<Packet>) -> IoResult<()> {
+		// every whole frame went out with its packet, a partial one left in the parser is dropped
+		let Some(packet) = packet else {
+			self.finished = true;
+			return Ok(());
+		};
 
-		if let Some(frame) = self.pending_frames.pop_front() {
-			return Ok(Some(frame));
+		if !packet.is_empty() {
+			self.parser.feed(&packet.data);
 		}
+		self.drain(packet.pts, packet.stream_index)
+	}
 
-		match self.parser.extract_frame()? {
-			Some((header, frame_data)) => {
-				Self::validate_header(&header)?;
-
-				let raw_aac = Self::extract_raw_aac_frame(&frame_data, &header);
-				let frame = Self::create_frame(raw_aac, &header, 0, 0)?;
-
-				Ok(Some(frame))
-			}
-			None => Ok(None),
+	fn receive_frame(&mut self) -> IoResult<Receive<Frame>> {
+		match self.pending_frames.pop_front() {
+			Some(frame) => Ok(Receive::Ready(frame)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
 		}
 	}
 }
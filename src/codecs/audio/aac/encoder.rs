@@ -1,5 +1,9 @@
-use crate::core::traits::Encoder;
-use crate::core::{AudioFormat, Frame, FrameAudio, FrameData, Packet, Time};
+use std::collections::VecDeque;
+
+use crate::core::frame::{AudioFormat, Frame, FrameAudio, FrameData};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::traits::{Encoder, Receive};
 use crate::io::Result as IoResult;
 use crate::io::{Error, ErrorKind};
 
@@ -13,6 +17,8 @@ pub struct AACEncoder {
 	bit_rate: u32,
 	frame_count: u64,
 	stream_index: usize,
+	queue: VecDeque<Packet>,
+	finished: bool,
 }
 
 impl AACEncoder {
@@ -38,7 +44,16 @@ impl AACEncoder {
 			return Err(Error::with_message(ErrorKind::InvalidData, "Unsupported sample rate for AAC"));
 		}
 
-		Ok(Self { sample_rate, channels, profile, bit_rate, frame_count: 0, stream_index: 0 })
+		Ok(Self {
+			sample_rate,
+			channels,
+			profile,
+			bit_rate,
+			frame_count: 0,
+			stream_index: 0,
+			queue: VecDeque::new(),
+			finished: false,
+		})
 	}
 
 	pub fn set_stream_index(&mut self, index: usize) {
@@ -97,10 +112,8 @@ impl AACEncoder {
 			_ => Err(Error::with_message(ErrorKind::InvalidData, "AAC encoder requires PCM input")),
 		}
 	}
-}
 
-impl Encoder for AACEncoder {
-	fn encode(&mut self, frame: Frame) -> IoResult<Option<Packet>> {
+	fn encode(&mut self, frame: Frame) -> IoResult<Packet> {
 		match frame.data {
 			FrameData::Audio(audio) => {
 				self.validate_frame(&audio)?;
@@ -118,14 +131,31 @@ impl Encoder for AACEncoder {
 				self.frame_count += 1;
 
 				let time = Time::new(1, self.sample_rate);
-				Ok(Some(Packet::new(packet_data, self.stream_index, time).with_pts(pts).with_dts(pts)))
+				Ok(Packet::new(packet_data, self.stream_index, time).with_pts(pts).with_dts(pts))
 			}
 			_ => Err(Error::with_message(ErrorKind::InvalidData, "AAC encoder expects audio frame")),
 		}
 	}
+}
 
-	fn flush(&mut self) -> IoResult<Option<Packet>> {
-		Ok(None)
+impl Encoder for AACEncoder {
+	fn send_frame(&mut self, frame: Option<Frame>) -> IoResult<()> {
+		match frame {
+			Some(frame) => {
+				let packet = self.encode(frame)?;
+				self.queue.push_back(packet);
+			}
+			None => self.finished = true,
+		}
+		Ok(())
+	}
+
+	fn receive_packet(&mut self) -> IoResult<Receive<Packet>> {
+		match self.queue.pop_front() {
+			Some(packet) => Ok(Receive::Ready(packet)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
 	}
 }
 
@@ -0,0 +1,36 @@
+use crate::container::wav::WavFormat;
+use crate::io::Result;
+
+// how the samples of one block are laid out, the same for decoding and encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockLayout {
+	pub channels: u8,
+	pub block_align: usize,
+	// per channel
+	pub samples_per_block: usize,
+}
+
+impl BlockLayout {
+	pub fn new(channels: u8, block_align: usize, samples_per_block: usize) -> Self {
+		Self { channels: std::cmp::max(1, channels), block_align, samples_per_block }
+	}
+
+	pub fn from_format(format: &WavFormat) -> Self {
+		Self::new(format.channels, format.block_align() as usize, format.block_samples() as usize)
+	}
+
+	pub fn block_length(&self) -> usize {
+		self.samples_per_block * self.channels as usize
+	}
+}
+
+// one adpcm flavour, `BlockDecoder` and `BlockEncoder` do the packets around it
+pub trait BlockCodec {
+	fn layout(&self) -> BlockLayout;
+
+	// interleaved samples of one block, a short last block gives what it holds
+	fn decode_block(&self, block: &[u8]) -> Result<Vec<i16>>;
+
+	// a full block of interleaved samples, past `real_samples` per channel they are padding
+	fn encode_block(&mut self, samples: &[i16], real_samples: usize) -> Vec<u8>;
+}
@@ -1,108 +1,74 @@
-use crate::core::frame::FrameAudio;
-use crate::core::traits::Decoder;
-use crate::io::Result as IoResult;
-use crate::io::{Error, ErrorKind};
-
-use super::state::AdpcmState;
-
-pub struct AdpcmDecoder {
+use std::collections::VecDeque;
+
+use super::block::BlockCodec;
+use crate::core::frame::{AudioFormat, Frame, FrameAudio};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::traits::{Decoder, Receive};
+use crate::io::Result;
+
+// turns packets of whole blocks into 16 bit frames
+pub struct BlockDecoder<C: BlockCodec> {
+	codec: C,
 	sample_rate: u32,
-	channels: u8,
-	states: Vec<AdpcmState>,
-	#[allow(dead_code)]
-	block_size: usize,
-	samples_per_block: usize,
+	// samples per channel in the stream, the padding of the last block is dropped past it
+	total_samples: Option<u64>,
+	queue: VecDeque<Frame>,
+	finished: bool,
 }
 
-impl AdpcmDecoder {
-	pub fn new(sample_rate: u32, channels: u8, block_size: usize) -> Self {
-		let channels = std::cmp::max(1, channels);
-		let states = vec![AdpcmState::new(); channels as usize];
-
-		// IMA ADPCM: 4 bits per sample
-		// block_align = 1 (sync nibbles) + (block_size - 1) encoded nibbles
-		let samples_per_block = ((block_size - 4) * 2) + 1;
-
-		Self { sample_rate, channels, states, block_size, samples_per_block }
+impl<C: BlockCodec> BlockDecoder<C> {
+	pub fn new(codec: C, sample_rate: u32) -> Self {
+		Self { codec, sample_rate, total_samples: None, queue: VecDeque::new(), finished: false }
 	}
 
-	fn decode_block(&mut self, block_data: &[u8]) -> IoResult<Vec<u8>> {
-		if block_data.len() < 4 * self.channels as usize {
-			return Err(Error::with_message(
-				ErrorKind::InvalidData,
-				"ADPCM block too small for sync header",
-			));
-		}
-
-		let mut pcm_output = Vec::new();
-		let mut offset = 0;
-
-		for ch in 0..self.channels as usize {
-			if offset + 4 > block_data.len() {
-				break;
-			}
-
-			let predictor = i16::from_le_bytes([block_data[offset], block_data[offset + 1]]);
-			let index = block_data[offset + 2];
-
-			self.states[ch] = AdpcmState::with_initial_values(predictor, index);
-			offset += 4;
-		}
-
-		let remaining = &block_data[offset..];
-		let mut nibble_idx = 0;
-
-		while nibble_idx < remaining.len() * 2
-			&& pcm_output.len() < self.samples_per_block * 2 * self.channels as usize
-		{
-			for channel in 0..self.channels as usize {
-				let byte_idx = nibble_idx / 2;
-
-				if byte_idx >= remaining.len() {
-					break;
-				}
-
-				let nibble = if nibble_idx % 2 == 0 {
-					remaining[byte_idx] & 0x0F
-				} else {
-					(remaining[byte_idx] >> 4) & 0x0F
-				};
-
-				let sample = self.states[channel].decode_nibble(nibble);
-				pcm_output.extend_from_slice(&sample.to_le_bytes());
-
-				nibble_idx += 1;
-			}
-		}
-
-		Ok(pcm_output)
+	pub fn with_total_samples(mut self, total_samples: Option<u64>) -> Self {
+		self.total_samples = total_samples;
+		self
 	}
-}
 
-impl Decoder for AdpcmDecoder {
-	fn decode(&mut self, packet: Packet) -> IoResult<Option<Frame>> {
-		if packet.is_empty() {
-			return Ok(None);
+	fn decode(&self, packet: Packet) -> Result<Option<Frame>> {
+		let layout = self.codec.layout();
+		let mut samples = Vec::new();
+		for block in packet.data.chunks(layout.block_align) {
+			samples.extend(self.codec.decode_block(block)?);
 		}
 
-		let pcm_data = self.decode_block(&packet.data)?;
-
-		if pcm_data.is_empty() {
+		let mut nb_samples = samples.len() / layout.channels as usize;
+		if let Some(total) = self.total_samples {
+			let left = total.saturating_sub(std::cmp::max(0, packet.pts) as u64);
+			nb_samples = std::cmp::min(nb_samples as u64, left) as usize;
+		}
+		if nb_samples == 0 {
 			return Ok(None);
 		}
 
-		let nb_samples = pcm_data.len() / (self.channels as usize * 2);
-
-		let audio = FrameAudio::new(pcm_data, self.sample_rate, self.channels, AudioFormat::PCM16);
+		let data = samples[..nb_samples * layout.channels as usize]
+			.iter()
+			.flat_map(|sample| sample.to_le_bytes())
+			.collect();
+		let audio = FrameAudio::new(data, self.sample_rate, layout.channels, AudioFormat::PCM16);
+		let audio = audio.with_nb_samples(nb_samples);
 
 		let time = Time::new(1, self.sample_rate);
+		Ok(Some(Frame::new_audio(audio, time, packet.stream_index, 0).with_pts(packet.pts)))
+	}
+}
 
-		let frame = Frame::new_audio(audio.with_nb_samples(nb_samples), time, packet.stream_index, 0);
-
-		Ok(Some(frame.with_pts(packet.pts)))
+impl<C: BlockCodec> Decoder for BlockDecoder<C> {
+	fn send_packet(&mut self, packet: Option<Packet>) -> Result<()> {
+		match packet {
+			Some(packet) => self.queue.extend(self.decode(packet)?),
+			None => self.finished = true,
+		}
+		Ok(())
 	}
 
-	fn flush(&mut self) -> IoResult<Option<Frame>> {
-		Ok(None)
+	fn receive_frame(&mut self) -> Result<Receive<Frame>> {
+		match self.queue.pop_front() {
+			Some(frame) => Ok(Receive::Ready(frame)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
 	}
 }
@@ -1,130 +1,105 @@
-use crate::container::wav::WavFormat;
-use crate::core::traits::Encoder;
-use crate::core::{Frame, Packet, Time};
-use crate::io::Result as IoResult;
-use crate::io::{Error, ErrorKind};
-
-use super::state::AdpcmState;
-
-pub struct AdpcmEncoder {
+use std::collections::VecDeque;
+
+use super::block::BlockCodec;
+use crate::core::frame::{AudioFormat, Frame, sample};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::{Encoder, Receive};
+use crate::io::{Error, Result};
+
+// fills whole blocks, the last one is padded with silence and its packet says how much is real
+pub struct BlockEncoder<C: BlockCodec> {
+	codec: C,
 	sample_rate: u32,
-	channels: u8,
-	states: Vec<AdpcmState>,
-	block_size: usize,
-	samples_per_block: usize,
+	// interleaved samples waiting for a full block
 	buffer: Vec<i16>,
+	next_pts: Option<i64>,
+	// taken from the first frame like the pts, the padded last block has no frame of its own
+	stream_index: usize,
+	queue: VecDeque<Packet>,
+	finished: bool,
 }
 
-impl AdpcmEncoder {
-	pub fn new(sample_rate: u32, channels: u8, block_size: usize) -> Self {
-		let channels = std::cmp::max(1, channels);
-		let states = vec![AdpcmState::new(); channels as usize];
-
-		// IMA ADPCM: samples_per_block = ((block_size - 4*channels) * 2) + 1
-		let samples_per_block = ((block_size.saturating_sub(4 * channels as usize)) * 2) + 1;
-
-		Self { sample_rate, channels, states, block_size, samples_per_block, buffer: Vec::new() }
-	}
-
-	pub fn new_from_metadata(metadata: &WavFormat) -> Self {
-		Self::new(metadata.sample_rate, metadata.channels, metadata.bytes_per_sample())
-	}
-
-	fn encode_block(&mut self, samples: &[i16]) -> IoResult<Vec<u8>> {
-		let mut output = Vec::new();
-
-		// Write sync header (predictor + index) for each channel
-		for ch in 0..self.channels as usize {
-			output.extend_from_slice(&self.states[ch].predictor.to_le_bytes());
-			output.push(self.states[ch].index);
-			output.push(0); // reserved
+impl<C: BlockCodec> BlockEncoder<C> {
+	pub fn new(codec: C, sample_rate: u32) -> Self {
+		Self {
+			codec,
+			sample_rate,
+			buffer: Vec::new(),
+			next_pts: None,
+			stream_index: 0,
+			queue: VecDeque::new(),
+			finished: false,
 		}
+	}
 
-		// Encode samples as nibbles
-		let mut nibbles = Vec::new();
-		let mut sample_idx = 0;
-
-		while sample_idx < samples.len() {
-			for channel in 0..self.channels as usize {
-				if sample_idx + channel >= samples.len() {
-					break;
-				}
+	fn emit_block(&mut self, real_samples: usize) {
+		let layout = self.codec.layout();
+		let samples: Vec<i16> = self.buffer.drain(..layout.block_length()).collect();
+		let data = self.codec.encode_block(&samples, real_samples);
 
-				let sample = samples[sample_idx + channel];
-				let code = self.states[channel].encode_sample(sample);
-				nibbles.push(code);
-			}
+		let pts = self.next_pts.unwrap_or(0);
+		self.next_pts = Some(pts + layout.samples_per_block as i64);
+		let time = Time::new(1, self.sample_rate);
+		let packet = Packet::new(data, self.stream_index, time).with_pts(pts).with_dts(pts);
+		self.queue.push_back(packet.with_duration(real_samples as i64));
+	}
 
-			sample_idx += self.channels as usize;
+	fn encode(&mut self, frame: Frame) -> Result<()> {
+		let Some(audio) = frame.audio() else {
+			return Ok(());
+		};
+		let layout = self.codec.layout();
+		if audio.channels != layout.channels {
+			let message =
+				format!("adpcm encoder expects {} channels, got {}", layout.channels, audio.channels);
+			return Err(Error::invalid_data(message));
 		}
 
-		// Pack nibbles into bytes (2 nibbles per byte)
-		for i in (0..nibbles.len()).step_by(2) {
-			let low = nibbles[i];
-			let high = if i + 1 < nibbles.len() { nibbles[i + 1] } else { 0 };
-			output.push((high << 4) | (low & 0x0F));
+		let pcm = match audio.format {
+			AudioFormat::PCM16 => audio.data.clone(),
+			_ => sample::from_f32(&audio.to_f32()?, AudioFormat::PCM16)?,
+		};
+		if self.next_pts.is_none() {
+			self.next_pts = Some(frame.pts);
+			self.stream_index = frame.stream_index;
 		}
+		self.buffer.extend(pcm.chunks_exact(2).map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]])));
 
-		// Pad to block_size if needed
-		while output.len() < self.block_size {
-			output.push(0);
+		while self.buffer.len() >= layout.block_length() {
+			self.emit_block(layout.samples_per_block);
 		}
+		Ok(())
+	}
 
-		if output.len() > self.block_size {
-			output.truncate(self.block_size);
+	fn finish(&mut self) {
+		if self.buffer.is_empty() {
+			return;
 		}
-
-		Ok(output)
+		let layout = self.codec.layout();
+		let real_samples = self.buffer.len() / layout.channels as usize;
+		self.buffer.resize(layout.block_length(), 0);
+		self.emit_block(real_samples);
 	}
 }
 
-impl Encoder for AdpcmEncoder {
-	fn encode(&mut self, frame: Frame) -> IoResult<Option<Packet>> {
-		let audio = frame
-			.audio()
-			.ok_or_else(|| Error::with_message(ErrorKind::InvalidData, "expected audio frame"))?;
-
-		// Convert frame data to i16 samples
-		if audio.data.len() % 2 != 0 {
-			return Err(Error::with_message(ErrorKind::InvalidData, "audio data length must be even"));
-		}
-
-		let samples: Vec<i16> =
-			audio.data.chunks_exact(2).map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]])).collect();
-
-		// Accumulate samples in buffer
-		self.buffer.extend_from_slice(&samples);
-
-		// Encode when we have enough samples for a block
-		if self.buffer.len() >= self.samples_per_block {
-			let block_samples: Vec<i16> = self.buffer.drain(0..self.samples_per_block).collect();
-			let encoded = self.encode_block(&block_samples)?;
-
-			let time = Time::new(1, self.sample_rate);
-			let packet = Packet::new(encoded, frame.stream_index, time).with_pts(frame.pts);
-
-			Ok(Some(packet))
-		} else {
-			Ok(None)
+impl<C: BlockCodec> Encoder for BlockEncoder<C> {
+	fn send_frame(&mut self, frame: Option<Frame>) -> Result<()> {
+		match frame {
+			Some(frame) => self.encode(frame)?,
+			None => {
+				self.finish();
+				self.finished = true;
+			}
 		}
+		Ok(())
 	}
 
-	fn flush(&mut self) -> IoResult<Option<Packet>> {
-		if self.buffer.is_empty() {
-			return Ok(None);
-		}
-
-		// Pad remaining samples with zeros
-		while self.buffer.len() < self.samples_per_block {
-			self.buffer.push(0);
+	fn receive_packet(&mut self) -> Result<Receive<Packet>> {
+		match self.queue.pop_front() {
+			Some(packet) => Ok(Receive::Ready(packet)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
 		}
-
-		let samples_to_encode: Vec<i16> = self.buffer.drain(..).collect();
-		let encoded = self.encode_block(&samples_to_encode)?;
-
-		let time = Time::new(1, self.sample_rate);
-		let packet = Packet::new(encoded, 0, time);
-
-		Ok(Some(packet))
 	}
 }
@@ -0,0 +1,86 @@
+use super::block::{BlockCodec, BlockLayout};
+use super::state::AdpcmState;
+use crate::io::{Error, Result};
+
+// IMA ADPCM as stored in wav: every channel opens a block with its first sample and step index,
+// then the channels take turns with 4 bytes, 8 samples, low nibble first
+pub struct ImaAdpcm {
+	layout: BlockLayout,
+	// the encoder's step index carries over from one block to the next
+	states: Vec<AdpcmState>,
+}
+
+impl ImaAdpcm {
+	pub fn new(layout: BlockLayout) -> Self {
+		Self { layout, states: vec![AdpcmState::new(); layout.channels as usize] }
+	}
+}
+
+impl BlockCodec for ImaAdpcm {
+	fn layout(&self) -> BlockLayout {
+		self.layout
+	}
+
+	fn decode_block(&self, block: &[u8]) -> Result<Vec<i16>> {
+		let channels = self.layout.channels as usize;
+		let samples_per_block = self.layout.samples_per_block;
+		if block.len() < 4 * channels {
+			return Err(Error::invalid_data("IMA ADPCM block too small for its header"));
+		}
+
+		let mut samples = vec![0i16; samples_per_block * channels];
+		let mut states = Vec::with_capacity(channels);
+		for (channel, header) in block.chunks_exact(4).take(channels).enumerate() {
+			let predictor = i16::from_le_bytes([header[0], header[1]]);
+			if header[2] > 88 {
+				return Err(Error::invalid_data("IMA ADPCM step index out of range"));
+			}
+			states.push(AdpcmState::with_initial_values(predictor, header[2]));
+			samples[channel] = predictor;
+		}
+
+		let mut decoded = 1;
+		for group in block[4 * channels..].chunks_exact(4 * channels) {
+			if decoded >= samples_per_block {
+				break;
+			}
+			for (channel, bytes) in group.chunks_exact(4).enumerate() {
+				let nibbles = bytes.iter().flat_map(|byte| [byte & 0x0F, byte >> 4]);
+				for (offset, nibble) in nibbles.enumerate() {
+					let index = decoded + offset;
+					if index < samples_per_block {
+						samples[index * channels + channel] = states[channel].decode_nibble(nibble);
+					}
+				}
+			}
+			decoded += 8;
+		}
+
+		samples.truncate(std::cmp::min(decoded, samples_per_block) * channels);
+		Ok(samples)
+	}
+
+	// the first sample of every channel goes into the header as it is
+	fn encode_block(&mut self, samples: &[i16], _real_samples: usize) -> Vec<u8> {
+		let channels = self.layout.channels as usize;
+		let mut block = Vec::with_capacity(self.layout.block_align);
+		for (channel, state) in self.states.iter_mut().enumerate() {
+			state.predictor = samples[channel];
+			block.extend(state.predictor.to_le_bytes());
+			block.extend([state.index, 0]);
+		}
+
+		for group in 0..(self.layout.samples_per_block - 1) / 8 {
+			for (channel, state) in self.states.iter_mut().enumerate() {
+				for pair in 0..4 {
+					let index = 1 + group * 8 + pair * 2;
+					let low = state.encode_sample(samples[index * channels + channel]);
+					let high = state.encode_sample(samples[(index + 1) * channels + channel]);
+					block.push(low | (high << 4));
+				}
+			}
+		}
+		block.resize(self.layout.block_align, 0);
+		block
+	}
+}
@@ -1,8 +1,147 @@
+pub mod block;
 pub mod decoder;
 pub mod encoder;
+pub mod ima;
+pub mod ms;
 pub mod state;
 pub mod table;
-// pub mod utils;
-pub use decoder::AdpcmDecoder;
-pub use encoder::AdpcmEncoder;
-// pub use utils::AdpcmState;
+
+pub use block::{BlockCodec, BlockLayout};
+pub use decoder::BlockDecoder;
+pub use encoder::BlockEncoder;
+pub use ima::ImaAdpcm;
+pub use ms::MsAdpcm;
+pub use state::{AdpcmState, MsAdpcmState};
+
+use crate::codecs::audio::{ADPCM_IMA_WAV, ADPCM_MS};
+use crate::container::wav::WavFormat;
+use crate::core::registry::{CodecEntry, Registry};
+use crate::core::stream::{Stream, StreamKind};
+use crate::core::{Decoder, Encoder};
+use crate::io::{Error, Result};
+
+pub fn register(registry: &mut Registry) {
+	for codec in [ADPCM_IMA_WAV, ADPCM_MS] {
+		let entry = CodecEntry::new(codec, StreamKind::Audio)
+			.with_decoder(create_decoder)
+			.with_encoder(create_encoder);
+		registry.register_codec(entry);
+	}
+}
+
+// the coefficient pairs of an MS ADPCM fmt extension, which follow the samples per block
+// and their count, the standard ones when the extension stops short of them
+pub fn ms_coefficients(extension: &[u8]) -> Result<Vec<[i16; 2]>> {
+	let word = |offset: usize| i16::from_le_bytes([extension[offset], extension[offset + 1]]);
+	if extension.len() < 4 {
+		return Ok(table::MS_COEFFICIENTS.to_vec());
+	}
+	let count = word(2) as u16 as usize;
+	if count == 0 || extension.len() < 4 + 4 * count {
+		return Err(Error::invalid_data("MS ADPCM coefficient table does not fit the fmt chunk"));
+	}
+	Ok((0..count).map(|index| [word(4 + 4 * index), word(6 + 4 * index)]).collect())
+}
+
+// the block layout comes from the fmt chunk, or is chosen by the format for a new file
+fn create_decoder(stream: &Stream) -> Result<Box<dyn Decoder>> {
+	let format = WavFormat::from_stream(stream)?;
+	let layout = BlockLayout::from_format(&format);
+	let total_samples = stream.duration.map(|duration| duration as u64);
+	match stream.codec.as_str() {
+		ADPCM_MS => {
+			let codec = MsAdpcm::new(layout, ms_coefficients(&stream.codec_private)?);
+			Ok(Box::new(BlockDecoder::new(codec, format.sample_rate).with_total_samples(total_samples)))
+		}
+		_ => {
+			let codec = ImaAdpcm::new(layout);
+			Ok(Box::new(BlockDecoder::new(codec, format.sample_rate).with_total_samples(total_samples)))
+		}
+	}
+}
+
+// the muxer writes the standard coefficients, so they are all the encoder picks from
+fn create_encoder(stream: &Stream) -> Result<Box<dyn Encoder>> {
+	let format = WavFormat::from_stream(stream)?;
+	let layout = BlockLayout::from_format(&format);
+	match stream.codec.as_str() {
+		ADPCM_MS => {
+			let codec = MsAdpcm::new(layout, table::MS_COEFFICIENTS.to_vec());
+			Ok(Box::new(BlockEncoder::new(codec, format.sample_rate)))
+		}
+		_ => Ok(Box::new(BlockEncoder::new(ImaAdpcm::new(layout), format.sample_rate))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::Receive;
+	use crate::core::frame::{AudioFormat, Frame, FrameAudio};
+	use crate::core::time::Time;
+
+	// stereo 8 kHz through 256 byte blocks and back, `kept` samples per channel open every block as
+	// they are and the rest stays within `tolerance`
+	fn roundtrip<C: BlockCodec>(encode: C, decode: C, samples: &[i16], kept: usize, tolerance: i32) {
+		let layout = encode.layout();
+		let spb = layout.samples_per_block;
+		let length = samples.len() / 2;
+		let data = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+		let audio = FrameAudio::new(data, 8000, 2, AudioFormat::PCM16);
+		// not the first stream, the padded last block has to stay on it too
+		let frame = Frame::new_audio(audio, Time::new(1, 8000), 1, 0);
+
+		let mut encoder = BlockEncoder::new(encode, 8000);
+		encoder.send_frame(Some(frame)).unwrap();
+		encoder.send_frame(None).unwrap();
+		let packets = encoder.receive_packets().unwrap();
+		let blocks = length.div_ceil(spb);
+		let pts: Vec<i64> = (0..blocks).map(|block| (block * spb) as i64).collect();
+		assert_eq!(packets.iter().map(|packet| packet.pts).collect::<Vec<_>>(), pts);
+		assert!(packets.iter().all(|packet| packet.data.len() == 256 && packet.stream_index == 1));
+		assert_eq!(packets[blocks - 1].duration, (length - (blocks - 1) * spb) as i64);
+
+		let mut decoder = BlockDecoder::new(decode, 8000).with_total_samples(Some(length as u64));
+		let mut decoded = Vec::new();
+		for packet in packets {
+			decoder.send_packet(Some(packet)).unwrap();
+			while let Receive::Ready(frame) = decoder.receive_frame().unwrap() {
+				decoded.extend(
+					frame.audio().unwrap().data.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])),
+				);
+			}
+		}
+		assert_eq!(decoded.len(), samples.len());
+		assert_eq!(decoded[spb * 2..(spb + kept) * 2], samples[spb * 2..(spb + kept) * 2]);
+		assert!(decoded.iter().zip(samples).all(|(a, b)| (*a as i32 - *b as i32).abs() < tolerance));
+	}
+
+	#[test]
+	fn test_blocks_roundtrip() {
+		// stereo 256 byte blocks hold 249 IMA samples per channel
+		let ramp: Vec<i16> = (0..600).flat_map(|n| [(n * 40) as i16, -(n * 40) as i16]).collect();
+		let layout = BlockLayout::new(2, 256, 249);
+		roundtrip(ImaAdpcm::new(layout), ImaAdpcm::new(layout), &ramp, 1, 200);
+
+		// or 244 MS samples
+		let triangle: Vec<i16> =
+			(0..500).flat_map(|n| [(n * 60) as i16, (((n % 100) - 50) as i16).abs() * 200]).collect();
+		let layout = BlockLayout::new(2, 256, 244);
+		let coefficients = ms_coefficients(&[]).unwrap();
+		roundtrip(
+			MsAdpcm::new(layout, coefficients.clone()),
+			MsAdpcm::new(layout, coefficients),
+			&triangle,
+			2,
+			600,
+		);
+	}
+
+	#[test]
+	fn test_ms_coefficients_from_extension() {
+		let extension = [0xF4, 0x01, 1, 0, 0x00, 0x01, 0x00, 0xFF];
+		assert_eq!(ms_coefficients(&extension).unwrap(), vec![[256, -256]]);
+		assert_eq!(ms_coefficients(&[0xF4, 0x01]).unwrap().len(), 7);
+		assert!(ms_coefficients(&[0xF4, 0x01, 2, 0, 0, 1, 0, 0]).is_err());
+	}
+}
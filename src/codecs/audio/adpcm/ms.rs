@@ -0,0 +1,110 @@
+use super::block::{BlockCodec, BlockLayout};
+use super::state::MsAdpcmState;
+use crate::io::{Error, Result};
+
+// Microsoft ADPCM as stored in wav: every channel opens a block with its predictor, step and
+// first two samples, then the nibbles alternate between the channels, high nibble first
+pub struct MsAdpcm {
+	layout: BlockLayout,
+	coefficients: Vec<[i16; 2]>,
+	// the encoder's step carries over from one block to the next
+	deltas: Vec<i32>,
+}
+
+impl MsAdpcm {
+	pub fn new(layout: BlockLayout, coefficients: Vec<[i16; 2]>) -> Self {
+		let layout =
+			BlockLayout { samples_per_block: std::cmp::max(2, layout.samples_per_block), ..layout };
+		Self { layout, coefficients, deltas: vec![16; layout.channels as usize] }
+	}
+
+	// the coefficients with the least squared error over the channel's real samples in the block
+	fn choose_predictor(&self, samples: &[i16], channel: usize, real_samples: usize) -> usize {
+		let channels = self.layout.channels as usize;
+		let errors = self.coefficients.iter().map(|&coefficients| {
+			let (first, second) = (samples[channels + channel], samples[channel]);
+			let mut state = MsAdpcmState::new(coefficients, self.deltas[channel], first, second);
+			let mut error = 0u64;
+			let real = samples[..real_samples * channels].iter();
+			for sample in real.skip(2 * channels + channel).step_by(channels) {
+				state.encode_sample(*sample);
+				error += ((*sample as i64 - state.sample1 as i64).pow(2)) as u64;
+			}
+			error
+		});
+		errors.enumerate().min_by_key(|(_, error)| *error).map(|(index, _)| index).unwrap_or(0)
+	}
+}
+
+impl BlockCodec for MsAdpcm {
+	fn layout(&self) -> BlockLayout {
+		self.layout
+	}
+
+	fn decode_block(&self, block: &[u8]) -> Result<Vec<i16>> {
+		let channels = self.layout.channels as usize;
+		let samples_per_block = self.layout.samples_per_block;
+		if block.len() < 7 * channels {
+			return Err(Error::invalid_data("MS ADPCM block too small for its header"));
+		}
+
+		let mut states = Vec::with_capacity(channels);
+		for channel in 0..channels {
+			let Some(&coefficients) = self.coefficients.get(block[channel] as usize) else {
+				return Err(Error::invalid_data("MS ADPCM predictor out of range"));
+			};
+			// after the predictors come the steps, then sample1 and sample2, a word per channel
+			let field = |number: usize| {
+				let offset = channels + 2 * (number * channels + channel);
+				i16::from_le_bytes([block[offset], block[offset + 1]])
+			};
+			states.push(MsAdpcmState::new(coefficients, field(0) as i32, field(1), field(2)));
+		}
+
+		let mut samples = Vec::with_capacity(samples_per_block * channels);
+		samples.extend(states.iter().map(|state| state.sample2));
+		samples.extend(states.iter().map(|state| state.sample1));
+
+		let nibbles = block[7 * channels..].iter().flat_map(|byte| [byte >> 4, byte & 0x0F]);
+		let length = samples_per_block.saturating_sub(2) * channels;
+		for (index, nibble) in nibbles.take(length).enumerate() {
+			samples.push(states[index % channels].decode_nibble(nibble));
+		}
+
+		let decoded = std::cmp::min(samples.len() / channels, samples_per_block);
+		samples.truncate(decoded * channels);
+		Ok(samples)
+	}
+
+	// the first two samples of every channel go into the header as they are
+	fn encode_block(&mut self, samples: &[i16], real_samples: usize) -> Vec<u8> {
+		let channels = self.layout.channels as usize;
+		let mut states = Vec::with_capacity(channels);
+		for channel in 0..channels {
+			let predictor = self.choose_predictor(samples, channel, real_samples);
+			let (first, second) = (samples[channels + channel], samples[channel]);
+			let state =
+				MsAdpcmState::new(self.coefficients[predictor], self.deltas[channel], first, second);
+			states.push((predictor, state));
+		}
+
+		let mut block = Vec::with_capacity(self.layout.block_align);
+		block.extend(states.iter().map(|(predictor, _)| *predictor as u8));
+		block.extend(states.iter().flat_map(|(_, state)| (state.delta as i16).to_le_bytes()));
+		block.extend(states.iter().flat_map(|(_, state)| state.sample1.to_le_bytes()));
+		block.extend(states.iter().flat_map(|(_, state)| state.sample2.to_le_bytes()));
+
+		let nibbles: Vec<u8> = samples[2 * channels..]
+			.iter()
+			.enumerate()
+			.map(|(index, sample)| states[index % channels].1.encode_sample(*sample))
+			.collect();
+		block.extend(nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair.get(1).unwrap_or(&0)));
+		block.resize(self.layout.block_align, 0);
+
+		for (delta, (_, state)) in self.deltas.iter_mut().zip(&states) {
+			*delta = state.delta;
+		}
+		block
+	}
+}
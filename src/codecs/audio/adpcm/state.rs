@@ -1,3 +1,6 @@
+use super::table::{ADAPTATION_TABLE, INDEX_TABLE, STEP_TABLE};
+
+// predictor and step index of one channel, the encoder mirrors the decoder so both stay in step
 #[derive(Debug, Clone, Copy)]
 pub struct AdpcmState {
 	pub predictor: i16,
@@ -13,87 +16,45 @@ impl AdpcmState {
 		Self { predictor, index: std::cmp::min(88, index) }
 	}
 
+	// the nibble whose reconstruction comes closest to the sample
 	pub fn encode_sample(&mut self, sample: i16) -> u8 {
-		let step = super::table::STEP_TABLE[self.index as usize];
-		let diff = sample.wrapping_sub(self.predictor);
-
-		let sign = if diff < 0 { 0x08 } else { 0x00 };
-		let abs_diff = diff.abs() as i32;
-
-		let mut code = sign as u8;
-		let mut temp_diff = abs_diff;
-		let mut temp_step = step;
-
-		// Encode the delta
-		if temp_diff >= temp_step {
-			code |= 0x04;
-			temp_diff -= temp_step;
-		}
-
-		temp_step >>= 1;
-		if temp_diff >= temp_step {
-			code |= 0x02;
-			temp_diff -= temp_step;
-		}
-
-		temp_step >>= 1;
-		if temp_diff >= temp_step {
-			code |= 0x01;
-		}
-
-		// Update predictor
-		let mut delta = step >> 3;
-		if code & 0x01 != 0 {
-			delta += step >> 2;
-		}
-		if code & 0x02 != 0 {
-			delta += step >> 1;
-		}
-		if code & 0x04 != 0 {
-			delta += step;
-		}
-
-		if code & 0x08 != 0 {
-			self.predictor = self.predictor.saturating_sub(delta as i16);
-		} else {
-			self.predictor = self.predictor.saturating_add(delta as i16);
+		let step = STEP_TABLE[self.index as usize];
+		let diff = sample as i32 - self.predictor as i32;
+
+		let mut code = if diff < 0 { 0x08 } else { 0x00 };
+		let mut remaining = diff.abs();
+		for (bit, step) in [(0x04, step), (0x02, step >> 1), (0x01, step >> 2)] {
+			if remaining >= step {
+				code |= bit;
+				remaining -= step;
+			}
 		}
 
-		// Update step index
-		let new_index = (self.index as i32) + super::table::INDEX_TABLE[(code & 0x0F) as usize] as i32;
-		self.index = std::cmp::max(0, std::cmp::min(88, new_index)) as u8;
-
+		self.decode_nibble(code);
 		code
 	}
 
 	pub fn decode_nibble(&mut self, code: u8) -> i16 {
-		let step = super::table::STEP_TABLE[self.index as usize];
+		let step = STEP_TABLE[self.index as usize];
 
-		// Decode the delta
 		let mut delta = step >> 3;
-		if code & 0x01 != 0 {
-			delta += step >> 2;
+		if code & 0x04 != 0 {
+			delta += step;
 		}
 		if code & 0x02 != 0 {
 			delta += step >> 1;
 		}
-		if code & 0x04 != 0 {
-			delta += step;
-		}
-
-		// Update predictor
-		if code & 0x08 != 0 {
-			self.predictor = self.predictor.saturating_sub(delta as i16);
-		} else {
-			self.predictor = self.predictor.saturating_add(delta as i16);
+		if code & 0x01 != 0 {
+			delta += step >> 2;
 		}
+		let predictor = match code & 0x08 {
+			0 => self.predictor as i32 + delta,
+			_ => self.predictor as i32 - delta,
+		};
+		self.predictor = predictor.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
 
-		// Clamp predictor to 16-bit range
-		self.predictor = std::cmp::max(-32768, std::cmp::min(32767, self.predictor));
-
-		// Update step index
-		let new_index = (self.index as i32) + super::table::INDEX_TABLE[(code & 0x0F) as usize] as i32;
-		self.index = std::cmp::max(0, std::cmp::min(88, new_index)) as u8;
+		let index = self.index as i32 + INDEX_TABLE[(code & 0x0F) as usize] as i32;
+		self.index = index.clamp(0, 88) as u8;
 
 		self.predictor
 	}
@@ -104,3 +65,44 @@ impl Default for AdpcmState {
 		Self::new()
 	}
 }
+
+// the last two samples and the step of one MS ADPCM channel
+#[derive(Debug, Clone, Copy)]
+pub struct MsAdpcmState {
+	pub coefficients: [i16; 2],
+	pub delta: i32,
+	pub sample1: i16,
+	pub sample2: i16,
+}
+
+impl MsAdpcmState {
+	pub fn new(coefficients: [i16; 2], delta: i32, sample1: i16, sample2: i16) -> Self {
+		Self { coefficients, delta, sample1, sample2 }
+	}
+
+	fn predict(&self) -> i32 {
+		let [first, second] = self.coefficients;
+		(self.sample1 as i32 * first as i32 + self.sample2 as i32 * second as i32) / 256
+	}
+
+	// the difference to the prediction in steps of delta, rounded
+	pub fn encode_sample(&mut self, sample: i16) -> u8 {
+		let diff = sample as i32 - self.predict();
+		let bias = if diff < 0 { -self.delta / 2 } else { self.delta / 2 };
+		let nibble = ((diff + bias) / self.delta).clamp(-8, 7);
+		let code = (nibble & 0x0F) as u8;
+		self.decode_nibble(code);
+		code
+	}
+
+	pub fn decode_nibble(&mut self, code: u8) -> i16 {
+		let nibble = ((code << 4) as i8 >> 4) as i32;
+		let sample = (self.predict() + nibble * self.delta).clamp(i16::MIN as i32, i16::MAX as i32);
+		self.sample2 = self.sample1;
+		self.sample1 = sample as i16;
+
+		let delta = (ADAPTATION_TABLE[(code & 0x0F) as usize] * self.delta) >> 8;
+		self.delta = delta.clamp(16, i32::MAX / 768);
+		self.sample1
+	}
+}
@@ -1,12 +1,19 @@
-/// IMA ADPCM step size table (standard ITU-T G.726 derivative)
-pub const STEP_TABLE: [i32; 90] = [
+/// IMA ADPCM step sizes, indexed by the step index (0 to 88)
+pub const STEP_TABLE: [i32; 89] = [
 	7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66, 73,
-	80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 305, 335, 369, 405, 445, 490,
-	540, 593, 651, 715, 783, 856, 935, 1024, 1121, 1230, 1350, 1480, 1622, 1778, 1949, 2139, 2356,
-	2600, 3000, 3400, 4000, 5200, 6000, 8000, 13000, 16000, 20000, 24000, 28000, 32000, 40000, 48000,
-	64000, 80000, 96000, 112000, 128000, 144000, 160000, 176000, 192000, 224000, 256000, 288000,
-	320000,
+	80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449, 494,
+	544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272, 2499,
+	2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493, 10442, 11487,
+	12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
 ];
 
 /// Index table for IMA ADPCM step size adjustment
 pub const INDEX_TABLE: [i8; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+/// MS ADPCM delta scale per nibble, in 1/256
+pub const ADAPTATION_TABLE: [i32; 16] =
+	[230, 230, 230, 230, 307, 409, 512, 614, 768, 614, 512, 409, 307, 230, 230, 230];
+
+/// MS ADPCM predictor coefficient pairs every file starts its table with, in 1/256
+pub const MS_COEFFICIENTS: [[i16; 2]; 7] =
+	[[256, 0], [512, -256], [0, 0], [192, 64], [240, 0], [460, -208], [392, -232]];
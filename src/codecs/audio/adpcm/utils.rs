@@ -1,3 +0,0 @@
-
-
-/// Decodes an ADPCM nibble to a PCM sample
@@ -22,10 +22,16 @@ pub const APE: &str = "ape";
 // pub const PCM_U8: &str = "pcm_u8";
 pub const PCM_S16LE: &str = "pcm_s16le";
 pub const PCM_S24LE: &str = "pcm_s24le";
+pub const PCM_S32LE: &str = "pcm_s32le";
 pub const PCM_F32LE: &str = "pcm_f32le";
-// pub const PCM_S32LE: &str = "pcm_s32le";
+pub const PCM_MULAW: &str = "pcm_mulaw";
+pub const PCM_ALAW: &str = "pcm_alaw";
 // pub const PCM_F64LE: &str = "pcm_f64le";
 
+// adpcm
+pub const ADPCM_IMA_WAV: &str = "adpcm_ima_wav";
+pub const ADPCM_MS: &str = "adpcm_ms";
+
 // misc / special
 pub const DSD_LSBF: &str = "dsd_lsbf";
 pub const DSD_MSBF: &str = "dsd_msbf";
@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+use super::Law;
+use crate::core::frame::{AudioFormat, Frame, FrameAudio};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::traits::{Decoder, Receive};
+use crate::io::Result;
+
+// expands every byte to a 16 bit sample
+pub struct G711Decoder {
+	law: Law,
+	sample_rate: u32,
+	channels: u8,
+	queue: VecDeque<Frame>,
+	finished: bool,
+}
+
+impl G711Decoder {
+	pub fn new(law: Law, sample_rate: u32, channels: u8) -> Self {
+		Self { law, sample_rate, channels, queue: VecDeque::new(), finished: false }
+	}
+
+	fn decode(&self, packet: Packet) -> Option<Frame> {
+		if packet.is_empty() {
+			return None;
+		}
+
+		let data: Vec<u8> =
+			packet.data.iter().flat_map(|&byte| self.law.decode(byte).to_le_bytes()).collect();
+		let nb_samples = packet.data.len() / self.channels as usize;
+
+		let audio = FrameAudio::new(data, self.sample_rate, self.channels, AudioFormat::PCM16);
+		let audio = audio.with_nb_samples(nb_samples);
+
+		let time = Time::new(1, self.sample_rate);
+		Some(Frame::new_audio(audio, time, packet.stream_index, 0).with_pts(packet.pts))
+	}
+}
+
+impl Decoder for G711Decoder {
+	fn send_packet(&mut self, packet: Option<Packet>) -> Result<()> {
+		match packet {
+			Some(packet) => self.queue.extend(self.decode(packet)),
+			None => self.finished = true,
+		}
+		Ok(())
+	}
+
+	fn receive_frame(&mut self) -> Result<Receive<Frame>> {
+		match self.queue.pop_front() {
+			Some(frame) => Ok(Receive::Ready(frame)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
+	}
+}
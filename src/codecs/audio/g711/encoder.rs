@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use super::Law;
+use crate::core::frame::{AudioFormat, Frame, sample};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::{Encoder, Receive};
+use crate::io::Result;
+
+// compands 16 bit samples, other sample formats are brought to 16 bits first
+pub struct G711Encoder {
+	law: Law,
+	sample_rate: u32,
+	queue: VecDeque<Packet>,
+	finished: bool,
+}
+
+impl G711Encoder {
+	pub fn new(law: Law, sample_rate: u32) -> Self {
+		Self { law, sample_rate, queue: VecDeque::new(), finished: false }
+	}
+
+	fn encode(&self, frame: Frame) -> Result<Option<Packet>> {
+		let Some(audio) = frame.audio() else {
+			return Ok(None);
+		};
+
+		let pcm = match audio.format {
+			AudioFormat::PCM16 => audio.data.clone(),
+			_ => sample::from_f32(&audio.to_f32()?, AudioFormat::PCM16)?,
+		};
+		let data = pcm
+			.chunks_exact(2)
+			.map(|bytes| self.law.encode(i16::from_le_bytes([bytes[0], bytes[1]])))
+			.collect();
+
+		let time = Time::new(1, self.sample_rate);
+		let packet = Packet::new(data, frame.stream_index, time);
+		Ok(Some(packet.with_pts(frame.pts).with_dts(frame.pts)))
+	}
+}
+
+impl Encoder for G711Encoder {
+	fn send_frame(&mut self, frame: Option<Frame>) -> Result<()> {
+		match frame {
+			Some(frame) => self.queue.extend(self.encode(frame)?),
+			None => self.finished = true,
+		}
+		Ok(())
+	}
+
+	fn receive_packet(&mut self) -> Result<Receive<Packet>> {
+		match self.queue.pop_front() {
+			Some(packet) => Ok(Receive::Ready(packet)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
+	}
+}
@@ -0,0 +1,237 @@
+pub mod decoder;
+pub mod encoder;
+
+pub use decoder::G711Decoder;
+pub use encoder::G711Encoder;
+
+use crate::codecs::audio::{PCM_ALAW, PCM_MULAW};
+use crate::core::registry::{CodecEntry, Registry};
+use crate::core::stream::{Stream, StreamKind};
+use crate::core::{Decoder, Encoder};
+use crate::io::{Error, Result};
+
+// the two companding laws of ITU-T G.711, one byte per sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Law {
+	MuLaw,
+	ALaw,
+}
+
+impl Law {
+	pub fn from_codec(codec: &str) -> Option<Self> {
+		match codec {
+			PCM_MULAW => Some(Self::MuLaw),
+			PCM_ALAW => Some(Self::ALaw),
+			_ => None,
+		}
+	}
+
+	pub fn decode(self, byte: u8) -> i16 {
+		match self {
+			Self::MuLaw => MULAW_DECODE[byte as usize],
+			Self::ALaw => ALAW_DECODE[byte as usize],
+		}
+	}
+
+	// µ-law keeps the top 14 bits of a sample, A-law the top 13
+	pub fn encode(self, sample: i16) -> u8 {
+		match self {
+			Self::MuLaw => MULAW_ENCODE[(sample as u16 >> 2) as usize],
+			Self::ALaw => ALAW_ENCODE[(sample as u16 >> 3) as usize],
+		}
+	}
+}
+
+pub fn register(registry: &mut Registry) {
+	for codec in [PCM_MULAW, PCM_ALAW] {
+		let entry = CodecEntry::new(codec, StreamKind::Audio)
+			.with_decoder(create_decoder)
+			.with_encoder(create_encoder);
+		registry.register_codec(entry);
+	}
+}
+
+fn law(stream: &Stream) -> Result<Law> {
+	Law::from_codec(&stream.codec)
+		.ok_or_else(|| Error::invalid_data(format!("{} is not a g711 codec", stream.codec)))
+}
+
+fn create_decoder(stream: &Stream) -> Result<Box<dyn Decoder>> {
+	let Some(audio) = stream.audio_parameters() else {
+		return Err(Error::invalid_data(format!("{} is not an audio stream", stream)));
+	};
+	Ok(Box::new(G711Decoder::new(law(stream)?, audio.sample_rate, audio.channels())))
+}
+
+fn create_encoder(stream: &Stream) -> Result<Box<dyn Encoder>> {
+	let Some(audio) = stream.audio_parameters() else {
+		return Err(Error::invalid_data(format!("{} is not an audio stream", stream)));
+	};
+	Ok(Box::new(G711Encoder::new(law(stream)?, audio.sample_rate)))
+}
+
+static MULAW_DECODE: [i16; 256] = decode_table(Law::MuLaw);
+static ALAW_DECODE: [i16; 256] = decode_table(Law::ALaw);
+static MULAW_ENCODE: [u8; 1 << 14] = mulaw_encode_table();
+static ALAW_ENCODE: [u8; 1 << 13] = alaw_encode_table();
+
+// the tables follow the reference implementation of ITU-T G.191
+const fn decode_table(law: Law) -> [i16; 256] {
+	let mut table = [0i16; 256];
+	let mut byte = 0;
+	while byte < 256 {
+		table[byte] = match law {
+			Law::MuLaw => mulaw_to_linear(byte as u8),
+			Law::ALaw => alaw_to_linear(byte as u8),
+		};
+		byte += 1;
+	}
+	table
+}
+
+const fn mulaw_to_linear(byte: u8) -> i16 {
+	let byte = !byte;
+	let magnitude = ((((byte & 0x0F) as i16) << 3) + 0x84) << ((byte & 0x70) >> 4);
+	if byte & 0x80 != 0 { 0x84 - magnitude } else { magnitude - 0x84 }
+}
+
+const fn alaw_to_linear(byte: u8) -> i16 {
+	let byte = byte ^ 0x55;
+	let mut magnitude = ((byte & 0x0F) as i16) << 4;
+	let segment = (byte & 0x70) >> 4;
+	magnitude += if segment == 0 { 8 } else { 0x108 };
+	if segment > 1 {
+		magnitude <<= segment - 1;
+	}
+	if byte & 0x80 != 0 { magnitude } else { -magnitude }
+}
+
+// index is the 14 bit two's complement sample, negative ones take the one's complement
+// as G.191 does, which is the same before or after dropping the low 2 bits
+const fn mulaw_encode_table() -> [u8; 1 << 14] {
+	let mut table = [0u8; 1 << 14];
+	let mut index = 0;
+	while index < 1 << 14 {
+		// sign extend the 14 bits
+		let sample = ((index as i16) << 2) >> 2;
+		let (mut magnitude, mask) = if sample < 0 { (!sample, 0x7F) } else { (sample, 0xFF) };
+		if magnitude > 8158 {
+			magnitude = 8158;
+		}
+		magnitude += 0x21;
+
+		let segment = segment(magnitude, 0x3F);
+		table[index] = if segment >= 8 {
+			0x7F ^ mask
+		} else {
+			(((segment as i16) << 4) | ((magnitude >> (segment + 1)) & 0x0F)) as u8 ^ mask
+		};
+		index += 1;
+	}
+	table
+}
+
+// index is the 13 bit two's complement sample
+const fn alaw_encode_table() -> [u8; 1 << 13] {
+	let mut table = [0u8; 1 << 13];
+	let mut index = 0;
+	while index < 1 << 13 {
+		let sample = ((index as i16) << 3) >> 3;
+		let (magnitude, mask) = if sample >= 0 { (sample, 0xD5) } else { (-sample - 1, 0x55) };
+
+		let segment = segment(magnitude, 0x1F);
+		table[index] = if segment >= 8 {
+			0x7F ^ mask
+		} else {
+			let shift = if segment < 2 { 1 } else { segment };
+			(((segment as i16) << 4) | ((magnitude >> shift) & 0x0F)) as u8 ^ mask
+		};
+		index += 1;
+	}
+	table
+}
+
+// first of the 8 segments whose end, doubling from `first_end`, holds the magnitude
+const fn segment(magnitude: i16, first_end: i16) -> u32 {
+	let mut segment = 0;
+	let mut end = first_end as i32;
+	while segment < 8 && magnitude as i32 > end {
+		segment += 1;
+		end = (end << 1) | 1;
+	}
+	segment
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// ulaw_compress and alaw_compress of the G.191 g711.c, one sample at a time
+	fn g191_ulaw_compress(sample: i16) -> u8 {
+		let absno = if sample < 0 { ((!sample) >> 2) + 33 } else { (sample >> 2) + 33 };
+		let absno = std::cmp::min(absno, 0x1FFF);
+		let mut i = absno >> 6;
+		let mut segno = 1;
+		while i != 0 {
+			segno += 1;
+			i >>= 1;
+		}
+		let high_nibble = 0x0008 - segno;
+		let low_nibble = 0x000F - ((absno >> segno) & 0x000F);
+		let log = (high_nibble << 4) | low_nibble;
+		if sample >= 0 { (log | 0x0080) as u8 } else { log as u8 }
+	}
+
+	fn g191_alaw_compress(sample: i16) -> u8 {
+		let mut ix = if sample < 0 { (!sample) >> 4 } else { sample >> 4 };
+		if ix > 15 {
+			let mut iexp = 1;
+			while ix > 16 + 15 {
+				ix >>= 1;
+				iexp += 1;
+			}
+			ix -= 16;
+			ix += iexp << 4;
+		}
+		if sample >= 0 {
+			ix |= 0x0080;
+		}
+		(ix ^ 0x0055) as u8
+	}
+
+	// ulaw_expand and alaw_expand
+	fn g191_ulaw_expand(log: u8) -> i16 {
+		let sign = if log < 0x80 { -1 } else { 1 };
+		let mantissa = !(log as i32);
+		let exponent = (mantissa >> 4) & 0x0007;
+		let step = 4 << (exponent + 1);
+		let mantissa = mantissa & 0x000F;
+		(sign * ((0x0080 << exponent) + step * mantissa + step / 2 - 4 * 33)) as i16
+	}
+
+	fn g191_alaw_expand(log: u8) -> i16 {
+		let ix = (log ^ 0x55) & 0x7F;
+		let iexp = ix >> 4;
+		let mut mant = (ix & 0x0F) as i16;
+		if iexp > 0 {
+			mant += 16;
+		}
+		mant = (mant << 4) + 0x0008;
+		if iexp > 1 {
+			mant <<= iexp - 1;
+		}
+		if log > 127 { mant } else { -mant }
+	}
+
+	#[test]
+	fn test_tables_match_g191() {
+		for sample in i16::MIN..=i16::MAX {
+			assert_eq!(Law::MuLaw.encode(sample), g191_ulaw_compress(sample), "µ-law {}", sample);
+			assert_eq!(Law::ALaw.encode(sample), g191_alaw_compress(sample), "A-law {}", sample);
+		}
+		for byte in 0..=255u8 {
+			assert_eq!(Law::MuLaw.decode(byte), g191_ulaw_expand(byte), "µ-law {:#04x}", byte);
+			assert_eq!(Law::ALaw.decode(byte), g191_alaw_expand(byte), "A-law {:#04x}", byte);
+		}
+	}
+}
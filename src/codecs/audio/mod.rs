@@ -1,5 +1,6 @@
 // pub mod aac;
-// pub mod adpcm;
+pub mod adpcm;
+pub mod g711;
 pub mod pcm;
 
 mod constants;
@@ -1,51 +1,104 @@
+use std::collections::VecDeque;
+
 use crate::container::wav::WavFormat;
 use crate::core::frame::{AudioFormat, Frame, FrameAudio};
 use crate::core::packet::Packet;
 use crate::core::time::Time;
-use crate::core::traits::Decoder;
+use crate::core::traits::{Decoder, Receive};
 use crate::io::Result as IoResult;
 
 pub struct PcmDecoder {
 	sample_rate: u32,
 	channels: u8,
 	bytes_per_sample: usize,
+	format: AudioFormat,
+	queue: VecDeque<Frame>,
+	finished: bool,
 }
 
 impl PcmDecoder {
 	pub fn new(sample_rate: u32, channels: u8, bytes_per_sample: usize) -> Self {
-		Self { sample_rate, channels, bytes_per_sample }
+		// 4 byte samples are taken for float unless the format says otherwise
+		let format = match bytes_per_sample {
+			3 => AudioFormat::PCM24,
+			4 => AudioFormat::PCM32,
+			_ => AudioFormat::PCM16,
+		};
+		Self {
+			sample_rate,
+			channels,
+			bytes_per_sample,
+			format,
+			queue: VecDeque::new(),
+			finished: false,
+		}
+	}
+
+	pub fn with_format(mut self, format: AudioFormat) -> Self {
+		self.format = format;
+		self
 	}
 
 	pub fn new_from_metadata(metadata: &WavFormat) -> Self {
-		Self::new(metadata.sample_rate, metadata.channels, metadata.bytes_per_sample())
+		let decoder = Self::new(metadata.sample_rate, metadata.channels, metadata.bytes_per_sample());
+		decoder.with_format(metadata.audio_format())
 	}
-}
 
-impl Decoder for PcmDecoder {
-	fn decode(&mut self, packet: Packet) -> IoResult<Option<Frame>> {
+	fn decode(&self, packet: Packet) -> Option<Frame> {
 		if packet.is_empty() {
-			return Ok(None);
+			return None;
 		}
 
 		let nb_samples = packet.data.len() / (self.channels as usize * self.bytes_per_sample);
 
-		let audio_format = match self.bytes_per_sample {
-			2 => AudioFormat::PCM16,
-			3 => AudioFormat::PCM24,
-			4 => AudioFormat::PCM32,
-			_ => AudioFormat::PCM16,
-		};
-
-		let audio = FrameAudio::new(packet.data, self.sample_rate, self.channels, audio_format);
+		let audio = FrameAudio::new(packet.data, self.sample_rate, self.channels, self.format);
 		let audio = audio.with_nb_samples(nb_samples);
 
 		let time = Time::new(1, self.sample_rate);
 		let frame = Frame::new_audio(audio, time, packet.stream_index, 0).with_pts(packet.pts);
 
-		Ok(Some(frame))
+		Some(frame)
+	}
+}
+
+impl Decoder for PcmDecoder {
+	fn send_packet(&mut self, packet: Option<Packet>) -> IoResult<()> {
+		match packet {
+			Some(packet) => self.queue.extend(self.decode(packet)),
+			None => self.finished = true,
+		}
+		Ok(())
+	}
+
+	fn receive_frame(&mut self) -> IoResult<Receive<Frame>> {
+		match self.queue.pop_front() {
+			Some(frame) => Ok(Receive::Ready(frame)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
 	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_send_receive_cycle() {
+		let mut decoder = PcmDecoder::new(8000, 2, 2);
+		let packet = Packet::new(vec![0; 16], 0, Time::new(1, 8000)).with_pts(40);
+
+		decoder.send_packet(Some(packet)).unwrap();
+		match decoder.receive_frame().unwrap() {
+			Receive::Ready(frame) => {
+				assert_eq!(frame.pts, 40);
+				assert_eq!(frame.audio().unwrap().nb_samples, 4);
+			}
+			other => panic!("expected a frame, got {:?}", other),
+		}
+		assert!(matches!(decoder.receive_frame().unwrap(), Receive::NeedMore));
 
-	fn flush(&mut self) -> IoResult<Option<Frame>> {
-		Ok(None)
+		decoder.send_packet(None).unwrap();
+		assert!(matches!(decoder.receive_frame().unwrap(), Receive::EndOfStream));
 	}
 }
@@ -1,18 +1,21 @@
-use crate::container::wav::{WavFormat, converter};
-use crate::core::Encoder;
-use crate::core::frame::{AudioFormat, Frame};
+use std::collections::VecDeque;
+
+use crate::core::frame::{AudioFormat, Frame, sample};
 use crate::core::packet::Packet;
 use crate::core::time::Time;
+use crate::core::{Encoder, Receive};
 use crate::io::Result;
 
 pub struct PcmEncoder {
 	sample_rate: u32,
 	target_format: Option<AudioFormat>,
+	queue: VecDeque<Packet>,
+	finished: bool,
 }
 
 impl PcmEncoder {
 	pub fn new(sample_rate: u32) -> Self {
-		Self { sample_rate, target_format: None }
+		Self { sample_rate, target_format: None, queue: VecDeque::new(), finished: false }
 	}
 
 	pub fn with_target_format(mut self, format: AudioFormat) -> Self {
@@ -20,20 +23,7 @@ impl PcmEncoder {
 		self
 	}
 
-	fn wav_format(format: AudioFormat, channels: u8, sample_rate: u32) -> WavFormat {
-		let bit_depth = match format {
-			AudioFormat::PCM16 => 16,
-			AudioFormat::PCM24 => 24,
-			AudioFormat::PCM32 => 32,
-			_ => 16,
-		};
-		let format_code = if bit_depth == 32 { 3 } else { 1 };
-		WavFormat { channels, sample_rate, bit_depth, format_code }
-	}
-}
-
-impl Encoder for PcmEncoder {
-	fn encode(&mut self, frame: Frame) -> Result<Option<Packet>> {
+	fn encode(&self, frame: Frame) -> Result<Option<Packet>> {
 		let audio = match frame.audio() {
 			Some(audio) => audio,
 			None => return Ok(None),
@@ -41,22 +31,33 @@ impl Encoder for PcmEncoder {
 
 		let time = Time::new(1, self.sample_rate);
 
-		if let Some(target) = self.target_format {
-			let format = Self::wav_format(audio.format, audio.channels, self.sample_rate);
-			let target_format = Self::wav_format(target, audio.channels, self.sample_rate);
-
-			let samples = converter::to_f32(&audio.data, &format)?;
-
-			let data = converter::from_f32(&samples, &target_format)?;
+		if let Some(target) = self.target_format
+			&& target != audio.format
+		{
+			let data = sample::from_f32(&audio.to_f32()?, target)?;
 			let packet = Packet::new(data, frame.stream_index, time);
-			return Ok(Some(packet.with_pts(frame.pts)));
+			return Ok(Some(packet.with_pts(frame.pts).with_dts(frame.pts)));
 		}
 
 		let packet = Packet::new(audio.data.clone(), frame.stream_index, time);
-		Ok(Some(packet.with_pts(frame.pts)))
+		Ok(Some(packet.with_pts(frame.pts).with_dts(frame.pts)))
 	}
+}
 
-	fn flush(&mut self) -> Result<Option<Packet>> {
-		Ok(None)
+impl Encoder for PcmEncoder {
+	fn send_frame(&mut self, frame: Option<Frame>) -> Result<()> {
+		match frame {
+			Some(frame) => self.queue.extend(self.encode(frame)?),
+			None => self.finished = true,
+		}
+		Ok(())
+	}
+
+	fn receive_packet(&mut self) -> Result<Receive<Packet>> {
+		match self.queue.pop_front() {
+			Some(packet) => Ok(Receive::Ready(packet)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
 	}
 }
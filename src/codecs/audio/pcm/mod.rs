@@ -3,3 +3,28 @@ pub mod encoder;
 
 pub use decoder::PcmDecoder;
 pub use encoder::PcmEncoder;
+
+use crate::codecs::audio::{PCM_F32LE, PCM_S16LE, PCM_S24LE, PCM_S32LE};
+use crate::container::wav::WavFormat;
+use crate::core::registry::{CodecEntry, Registry};
+use crate::core::stream::{Stream, StreamKind};
+use crate::core::{Decoder, Encoder};
+use crate::io::Result;
+
+pub fn register(registry: &mut Registry) {
+	for codec in [PCM_S16LE, PCM_S24LE, PCM_S32LE, PCM_F32LE] {
+		let entry = CodecEntry::new(codec, StreamKind::Audio)
+			.with_decoder(create_decoder)
+			.with_encoder(create_encoder);
+		registry.register_codec(entry);
+	}
+}
+
+fn create_decoder(stream: &Stream) -> Result<Box<dyn Decoder>> {
+	Ok(Box::new(PcmDecoder::new_from_metadata(&WavFormat::from_stream(stream)?)))
+}
+
+fn create_encoder(stream: &Stream) -> Result<Box<dyn Encoder>> {
+	let format = WavFormat::from_stream(stream)?;
+	Ok(Box::new(PcmEncoder::new(format.sample_rate).with_target_format(format.audio_format())))
+}
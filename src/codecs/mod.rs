@@ -4,3 +4,11 @@ pub mod video;
 
 pub const UNKNOWN: &str = "unknown";
 pub const RAW: &str = "raw";
+
+use crate::core::registry::Registry;
+
+pub fn register(registry: &mut Registry) {
+	audio::pcm::register(registry);
+	audio::g711::register(registry);
+	audio::adpcm::register(registry);
+}
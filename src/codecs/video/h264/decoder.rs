@@ -1,5 +1,10 @@
+use std::collections::VecDeque;
+
 use super::parser::{H264Parser, SpsData};
-use crate::core::{Decoder, Frame, FrameVideo, Packet, Time, VideoFormat};
+use crate::core::frame::{Frame, FrameVideo, VideoFormat};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::traits::{Decoder, Receive};
 use crate::io::{Error, Result};
 
 pub struct H264Decoder {
@@ -8,11 +13,21 @@ pub struct H264Decoder {
 	width: u32,
 	height: u32,
 	time_scale: u32,
+	queue: VecDeque<Frame>,
+	finished: bool,
 }
 
 impl H264Decoder {
 	pub fn new(time_scale: u32) -> Self {
-		Self { sps_data: None, frame_count: 0, width: 1280, height: 720, time_scale }
+		Self {
+			sps_data: None,
+			frame_count: 0,
+			width: 1280,
+			height: 720,
+			time_scale,
+			queue: VecDeque::new(),
+			finished: false,
+		}
 	}
 
 	pub fn with_dimensions(mut self, width: u32, height: u32) -> Self {
@@ -47,9 +62,7 @@ impl H264Decoder {
 
 		Ok(frame_bytes)
 	}
-}
 
-impl Decoder for H264Decoder {
 	fn decode(&mut self, packet: Packet) -> Result<Option<Frame>> {
 		let frame_data = Self::extract_frame_data(&packet)?;
 
@@ -81,8 +94,25 @@ impl Decoder for H264Decoder {
 
 		Ok(Some(frame))
 	}
+}
 
-	fn flush(&mut self) -> Result<Option<Frame>> {
-		Ok(None)
+impl Decoder for H264Decoder {
+	fn send_packet(&mut self, packet: Option<Packet>) -> Result<()> {
+		match packet {
+			Some(packet) => {
+				let frame = self.decode(packet)?;
+				self.queue.extend(frame);
+			}
+			None => self.finished = true,
+		}
+		Ok(())
+	}
+
+	fn receive_frame(&mut self) -> Result<Receive<Frame>> {
+		match self.queue.pop_front() {
+			Some(frame) => Ok(Receive::Ready(frame)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
 	}
 }
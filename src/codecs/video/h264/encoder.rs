@@ -1,5 +1,10 @@
+use std::collections::VecDeque;
+
 use super::utils::{NalUnit, NalUnitType, escape_emulation_prevention};
-use crate::core::{Encoder, Frame, FrameVideo, Packet, Time};
+use crate::core::frame::{Frame, FrameVideo};
+use crate::core::packet::Packet;
+use crate::core::time::Time;
+use crate::core::traits::{Encoder, Receive};
 use crate::io::Result;
 
 #[allow(dead_code)]
@@ -10,11 +15,22 @@ pub struct H264Encoder {
 	frame_count: u64,
 	sps_generated: bool,
 	pps_generated: bool,
+	queue: VecDeque<Packet>,
+	finished: bool,
 }
 
 impl H264Encoder {
 	pub fn new(width: u32, height: u32, time_scale: u32) -> Self {
-		Self { width, height, time_scale, frame_count: 0, sps_generated: false, pps_generated: false }
+		Self {
+			width,
+			height,
+			time_scale,
+			frame_count: 0,
+			sps_generated: false,
+			pps_generated: false,
+			queue: VecDeque::new(),
+			finished: false,
+		}
 	}
 
 	fn generate_sps(&self) -> NalUnit {
@@ -71,10 +87,8 @@ impl H264Encoder {
 
 		frame_data
 	}
-}
 
-impl Encoder for H264Encoder {
-	fn encode(&mut self, frame: Frame) -> Result<Option<Packet>> {
+	fn encode(&mut self, frame: Frame) -> Result<Packet> {
 		let video = frame.video().ok_or_else(|| crate::io::Error::invalid_data("not a video frame"))?;
 
 		let is_keyframe = video.keyframe;
@@ -105,10 +119,27 @@ impl Encoder for H264Encoder {
 
 		self.frame_count += 1;
 
-		Ok(Some(packet))
+		Ok(packet)
+	}
+}
+
+impl Encoder for H264Encoder {
+	fn send_frame(&mut self, frame: Option<Frame>) -> Result<()> {
+		match frame {
+			Some(frame) => {
+				let packet = self.encode(frame)?;
+				self.queue.push_back(packet);
+			}
+			None => self.finished = true,
+		}
+		Ok(())
 	}
 
-	fn flush(&mut self) -> Result<Option<Packet>> {
-		Ok(None)
+	fn receive_packet(&mut self) -> Result<Receive<Packet>> {
+		match self.queue.pop_front() {
+			Some(packet) => Ok(Receive::Ready(packet)),
+			None if self.finished => Ok(Receive::EndOfStream),
+			None => Ok(Receive::NeedMore),
+		}
 	}
 }
@@ -7,6 +7,7 @@ pub const OGV: &str = "ogv";
 pub const FLV: &str = "flv";
 pub const MXF: &str = "mxf";
 pub const TS: &str = "ts";
+pub const H264: &str = "h264";
 
 //
 pub const MP3: &str = "mp3";
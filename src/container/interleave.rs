@@ -0,0 +1,88 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crate::core::packet::Packet;
+
+// past this many queued packets the earliest one goes out even if a stream has nothing pending,
+// so a stream that holds everything back until the end cannot buffer the others forever
+pub const MAX_QUEUED: usize = 4096;
+
+// orders packets of several streams by dts before they reach the muxer
+pub struct Interleaver {
+	queues: Vec<VecDeque<Packet>>,
+	finished: Vec<bool>,
+	queued: usize,
+}
+
+impl Interleaver {
+	pub fn new(streams: usize) -> Self {
+		Self { queues: vec![VecDeque::new(); streams], finished: vec![false; streams], queued: 0 }
+	}
+
+	pub fn push(&mut self, packet: Packet) {
+		self.queued += 1;
+		self.queues[packet.stream_index].push_back(packet);
+	}
+
+	// no more packets will be pushed for `stream`
+	pub fn finish(&mut self, stream: usize) {
+		self.finished[stream] = true;
+	}
+
+	// the next packet in dts order, once no stream can still deliver an earlier one
+	pub fn pop(&mut self) -> Option<Packet> {
+		let waiting =
+			self.queues.iter().zip(&self.finished).any(|(queue, &done)| queue.is_empty() && !done);
+		if waiting && self.queued < MAX_QUEUED {
+			return None;
+		}
+
+		let earliest = self
+			.queues
+			.iter()
+			.enumerate()
+			.filter_map(|(index, queue)| queue.front().map(|packet| (index, packet)))
+			.min_by(|(_, a), (_, b)| compare_dts(a, b))
+			.map(|(index, _)| index)?;
+
+		self.queued -= 1;
+		self.queues[earliest].pop_front()
+	}
+}
+
+fn compare_dts(a: &Packet, b: &Packet) -> Ordering {
+	let a_time = a.dts as i128 * a.time.num as i128 * b.time.den as i128;
+	let b_time = b.dts as i128 * b.time.num as i128 * a.time.den as i128;
+	a_time.cmp(&b_time)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::time::Time;
+
+	fn packet(stream: usize, dts: i64, time: Time) -> Packet {
+		Packet::new(Vec::new(), stream, time).with_dts(dts)
+	}
+
+	#[test]
+	fn test_orders_across_time_bases() {
+		let audio = Time::new(1, 48000);
+		let video = Time::new(1, 25);
+		let mut interleaver = Interleaver::new(2);
+
+		interleaver.push(packet(0, 0, audio));
+		interleaver.push(packet(0, 24000, audio));
+		assert_eq!(interleaver.pop().map(|p| p.dts), None);
+
+		interleaver.push(packet(1, 0, video));
+		interleaver.push(packet(1, 10, video));
+		interleaver.push(packet(1, 20, video));
+		interleaver.finish(0);
+		interleaver.finish(1);
+
+		let order: Vec<_> =
+			std::iter::from_fn(|| interleaver.pop()).map(|p| (p.stream_index, p.dts)).collect();
+		assert_eq!(order, vec![(0, 0), (1, 0), (1, 10), (0, 24000), (1, 20)]);
+	}
+}
@@ -1,6 +1,21 @@
+pub mod interleave;
 pub mod mkv;
+pub mod probe;
 pub mod raw;
 pub mod wav;
 
 mod constants;
 pub use constants::*;
+
+use crate::core::registry::{ContainerEntry, Registry};
+
+pub fn register(registry: &mut Registry) {
+	wav::register(registry);
+	raw::register(registry);
+
+	// recognised by content so the error names the real format, nothing reads them yet
+	registry.register_container(ContainerEntry::new(MKV).with_probe(probe::probe_matroska));
+	registry.register_container(ContainerEntry::new(WEBM).with_probe(probe::probe_webm));
+	registry.register_container(ContainerEntry::new(AAC).with_probe(probe::probe_adts));
+	registry.register_container(ContainerEntry::new(H264).with_probe(probe::probe_annexb));
+}
@@ -0,0 +1,215 @@
+use crate::io::{MediaRead, Result};
+
+// how many leading bytes the probes get to look at
+pub const PROBE_SIZE: usize = 4096;
+
+pub const SCORE_MAX: u8 = 100;
+// below this a match is a guess and the file extension wins
+pub const SCORE_CONFIDENT: u8 = 50;
+
+// scores how likely `data`, the start of a stream, is in this format, 0 meaning not at all
+pub type ProbeFn = fn(&[u8]) -> u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeResult {
+	pub format: &'static str,
+	pub score: u8,
+}
+
+impl ProbeResult {
+	pub fn is_confident(&self) -> bool {
+		self.score >= SCORE_CONFIDENT
+	}
+}
+
+pub struct Prober {
+	probes: Vec<(&'static str, ProbeFn)>,
+}
+
+impl Prober {
+	pub fn empty() -> Self {
+		Self { probes: Vec::new() }
+	}
+
+	pub fn register(&mut self, format: &'static str, probe: ProbeFn) {
+		self.probes.push((format, probe));
+	}
+
+	// best scoring format, ties go to the first registered
+	pub fn probe_bytes(&self, data: &[u8]) -> Option<ProbeResult> {
+		let mut best: Option<ProbeResult> = None;
+		for (format, probe) in &self.probes {
+			let score = probe(data).min(SCORE_MAX);
+			if score > 0 && best.is_none_or(|best| score > best.score) {
+				best = Some(ProbeResult { format, score });
+			}
+		}
+		best
+	}
+
+	// reads up to PROBE_SIZE bytes, which are handed back so the caller can replay them
+	pub fn probe<R: MediaRead>(&self, reader: &mut R) -> Result<(Option<ProbeResult>, Vec<u8>)> {
+		let mut data = vec![0u8; PROBE_SIZE];
+		let mut filled = 0;
+		while filled < data.len() {
+			let read = reader.read(&mut data[filled..])?;
+			if read == 0 {
+				break;
+			}
+			filled += read;
+		}
+		data.truncate(filled);
+		Ok((self.probe_bytes(&data), data))
+	}
+}
+
+// RF64 and BW64 are the 64 bit variants
+pub fn probe_wav(data: &[u8]) -> u8 {
+	if data.len() < 12 || !matches!(&data[0..4], b"RIFF" | b"RF64" | b"BW64") {
+		return 0;
+	}
+	if &data[8..12] == b"WAVE" { SCORE_MAX } else { 10 }
+}
+
+fn ebml_doc_type(data: &[u8]) -> Option<&[u8]> {
+	if data.len() < 4 || data[0..4] != [0x1A, 0x45, 0xDF, 0xA3] {
+		return None;
+	}
+	// DocType element 0x4282 with a one byte size, well inside the ebml header
+	let header = &data[..data.len().min(64)];
+	let position = header.windows(2).position(|id| id == [0x42, 0x82])?;
+	let size = (*header.get(position + 2)? & 0x7F) as usize;
+	header.get(position + 3..position + 3 + size)
+}
+
+pub fn probe_matroska(data: &[u8]) -> u8 {
+	match ebml_doc_type(data) {
+		Some(b"matroska") => SCORE_MAX,
+		Some(_) => 0,
+		// an ebml header without a readable doc type is most likely matroska
+		None if data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) => SCORE_CONFIDENT,
+		None => 0,
+	}
+}
+
+pub fn probe_webm(data: &[u8]) -> u8 {
+	match ebml_doc_type(data) {
+		Some(b"webm") => SCORE_MAX,
+		_ => 0,
+	}
+}
+
+// length of the adts frame starting at `data`, None when there is no valid header
+fn adts_frame_length(data: &[u8]) -> Option<usize> {
+	if data.len() < 7 || data[0] != 0xFF || data[1] & 0xF6 != 0xF0 {
+		return None;
+	}
+	// sampling frequency index 13 and above is reserved
+	if (data[2] >> 2) & 0x0F > 12 {
+		return None;
+	}
+	let length =
+		((data[3] as usize & 0x03) << 11) | ((data[4] as usize) << 3) | (data[5] as usize >> 5);
+	if length < 7 { None } else { Some(length) }
+}
+
+pub fn probe_adts(data: &[u8]) -> u8 {
+	let mut position = 0;
+	let mut frames = 0;
+	while let Some(length) = data.get(position..).and_then(adts_frame_length) {
+		frames += 1;
+		position += length;
+		if position >= data.len() {
+			break;
+		}
+	}
+
+	match frames {
+		0 => 0,
+		1 => 25,
+		2 => 60,
+		_ => SCORE_MAX,
+	}
+}
+
+pub fn probe_annexb(data: &[u8]) -> u8 {
+	let mut nal_types = Vec::new();
+	let mut index = 0;
+	while index + 3 < data.len() {
+		if data[index..index + 3] == [0, 0, 1] {
+			let header = data[index + 3];
+			// the forbidden zero bit must be clear
+			if header & 0x80 != 0 {
+				return 0;
+			}
+			nal_types.push(header & 0x1F);
+			index += 3;
+		} else {
+			index += 1;
+		}
+	}
+
+	let leading = data.starts_with(&[0, 0, 1]) || data.starts_with(&[0, 0, 0, 1]);
+	let has = |nal: u8| nal_types.contains(&nal);
+	match () {
+		_ if !leading => 0,
+		_ if has(7) && has(8) => 80,
+		_ if has(7) || has(5) => 50,
+		_ if nal_types.len() > 1 => 20,
+		_ => 0,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::container;
+	use crate::core::registry::Registry;
+	use crate::io::Cursor;
+
+	fn adts_frame(length: usize) -> Vec<u8> {
+		let mut frame = vec![0u8; length];
+		frame[..7].copy_from_slice(&[
+			0xFF,
+			0xF1,
+			0x50,
+			0x80 | ((length >> 11) as u8 & 0x03),
+			(length >> 3) as u8,
+			((length as u8 & 0x07) << 5) | 0x1F,
+			0xFC,
+		]);
+		frame
+	}
+
+	#[test]
+	fn test_detects_formats_from_content() {
+		let prober = Registry::default().prober();
+
+		let mut wav = b"RIFF\x24\x00\x00\x00WAVEfmt ".to_vec();
+		wav.resize(64, 0);
+		assert_eq!(prober.probe_bytes(&wav), Some(ProbeResult { format: container::WAV, score: 100 }));
+
+		let adts: Vec<u8> = (0..3).flat_map(|_| adts_frame(200)).collect();
+		assert_eq!(prober.probe_bytes(&adts).unwrap().format, container::AAC);
+
+		let h264 = [0, 0, 0, 1, 0x67, 0x42, 0, 0x1E, 0, 0, 0, 1, 0x68, 0xCE, 0, 0, 1, 0x65, 0x88];
+		let result = prober.probe_bytes(&h264).unwrap();
+		assert_eq!(result.format, container::H264);
+		assert!(result.is_confident());
+
+		let webm = [0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x82, 0x84, b'w', b'e', b'b', b'm'];
+		assert_eq!(prober.probe_bytes(&webm).unwrap().format, container::WEBM);
+	}
+
+	#[test]
+	fn test_inconclusive_and_replay() {
+		let prober = Registry::default().prober();
+		assert_eq!(prober.probe_bytes(&[0x12, 0x34, 0x56, 0x78]), None);
+		assert!(!prober.probe_bytes(&adts_frame(5000)).unwrap().is_confident());
+
+		let data: Vec<u8> = (0..10000).map(|n| n as u8).collect();
+		let mut cursor = Cursor::new(data.clone());
+		let (_, head) = prober.probe(&mut cursor).unwrap();
+		assert_eq!(head, data[..PROBE_SIZE]);
+	}
+}
@@ -1,7 +1,7 @@
 use super::RawPcmFormat;
 use crate::core::packet::Packet;
-use crate::core::{Demuxer, stream, time};
-use crate::io::{MediaRead, Result};
+use crate::core::{Demuxer, SeekFlags, stream, time};
+use crate::io::{Error, MediaRead, MediaSeek, ReadPrimitives, Result, SeekFrom};
 
 pub struct RawPcmDemuxer<R: MediaRead> {
 	reader: R,
@@ -10,6 +10,7 @@ pub struct RawPcmDemuxer<R: MediaRead> {
 	data_remaining: Option<u64>,
 	packet_count: u64,
 	sample_position: u64,
+	end_position: Option<u64>,
 }
 
 impl<R: MediaRead> RawPcmDemuxer<R> {
@@ -18,18 +19,55 @@ impl<R: MediaRead> RawPcmDemuxer<R> {
 	pub fn new(reader: R, format: RawPcmFormat) -> Result<Self> {
 		let codec_name = format.to_codec_string().to_string();
 		let time = time::Time::new(1, format.sample_rate);
-		let stream = stream::Stream::new(0, 0, stream::StreamKind::Audio, codec_name, time);
+		let stream = stream::Stream::new(0, 0, stream::StreamKind::Audio, codec_name, time)
+			.with_parameters(format.parameters());
 		let streams = stream::Streams::new(vec![stream]);
 
-		Ok(Self { reader, format, streams, data_remaining: None, packet_count: 0, sample_position: 0 })
+		Ok(Self {
+			reader,
+			format,
+			streams,
+			data_remaining: None,
+			packet_count: 0,
+			sample_position: 0,
+			end_position: None,
+		})
+	}
+
+	// drops the data before `start` without reading it into packets and stops at `end`
+	pub fn with_sample_range(mut self, start: u64, end: Option<u64>) -> Result<Self> {
+		let frame_size = self.format.bytes_per_frame();
+		let mut remaining = start as usize * frame_size;
+		let mut buf = vec![0u8; std::cmp::min(remaining, Self::CHUNK_SIZE_LIMIT)];
+		while remaining > 0 {
+			let length = std::cmp::min(remaining, buf.len());
+			let bytes_read = self.reader.read(&mut buf[..length])?;
+			if bytes_read == 0 {
+				break;
+			}
+			remaining -= bytes_read;
+		}
+
+		self.sample_position = ((start as usize * frame_size - remaining) / frame_size) as u64;
+		self.end_position = end;
+		Ok(self)
 	}
 
 	pub fn read_packet(&mut self) -> Result<Option<Packet>> {
 		let block_align = self.format.block_align() as u64;
 		let max_chunk = (Self::CHUNK_SIZE_LIMIT as u64 / block_align) * block_align;
-		let chunk_size = max_chunk as usize;
+		let mut chunk_size = max_chunk;
+		if let Some(end) = self.end_position {
+			let left = end.saturating_sub(self.sample_position) * self.format.bytes_per_frame() as u64;
+			chunk_size = std::cmp::min(chunk_size, left);
+		}
+		if chunk_size == 0 {
+			return Ok(None);
+		}
+		let chunk_size = chunk_size as usize;
 		let mut data = vec![0u8; chunk_size];
-		let bytes_read = self.reader.read(&mut data)?;
+		let frame_size = self.format.bytes_per_frame();
+		let bytes_read = self.reader.read_to_fill(&mut data)? / frame_size * frame_size;
 
 		if bytes_read == 0 {
 			return Ok(None);
@@ -42,7 +80,8 @@ impl<R: MediaRead> RawPcmDemuxer<R> {
 		}
 
 		let time = time::Time::new(1, self.format.sample_rate);
-		let packet = Packet::new(data, 0, time).with_pts(self.sample_position as i64);
+		let position = self.sample_position as i64;
+		let packet = Packet::new(data, 0, time).with_pts(position).with_dts(position);
 
 		self.sample_position += (bytes_read / self.format.bytes_per_frame()) as u64;
 		self.packet_count += 1;
@@ -59,11 +98,56 @@ impl<R: MediaRead> RawPcmDemuxer<R> {
 	}
 }
 
-impl<R: MediaRead> Demuxer for RawPcmDemuxer<R> {
+impl<R: MediaRead + MediaSeek> RawPcmDemuxer<R> {
+	// headerless, every frame starts at a multiple of the frame size so any flag lands on the
+	// sample, past the end of the input it stops after the last whole frame
+	pub fn seek(&mut self, sample: i64, flags: SeekFlags) -> Result<i64> {
+		let frame_size = self.format.bytes_per_frame() as u64;
+		let current = self.reader.stream_position()?;
+		let start = current - self.sample_position * frame_size;
+		let total_frames = (self.reader.seek(SeekFrom::End(0))? - start) / frame_size;
+
+		let target = match flags {
+			SeekFlags::Any | SeekFlags::KeyframeBefore | SeekFlags::Exact => std::cmp::max(0, sample),
+		};
+		let target = std::cmp::min(target as u64, total_frames);
+		self.reader.seek(SeekFrom::Start(start + target * frame_size))?;
+		self.sample_position = target;
+		Ok(target as i64)
+	}
+}
+
+impl<R: MediaRead + MediaSeek> Demuxer for RawPcmDemuxer<R> {
 	fn streams(&self) -> &stream::Streams {
 		&self.streams
 	}
 	fn read_packet(&mut self) -> Result<Option<Packet>> {
 		self.read_packet()
 	}
+	fn seek(&mut self, stream_index: usize, timestamp: i64, flags: SeekFlags) -> Result<i64> {
+		if stream_index != 0 {
+			return Err(Error::invalid_data(format!("stream {} does not exist", stream_index)));
+		}
+		self.seek(timestamp, flags)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::io::Cursor;
+
+	#[test]
+	fn test_seek_stops_at_the_end() {
+		// 10 stereo 16 bit frames and a stray byte
+		let data: Vec<u8> = (0..41).collect();
+		let mut demuxer = RawPcmDemuxer::new(Cursor::new(data), RawPcmFormat::default()).unwrap();
+
+		assert_eq!(demuxer.seek(4, SeekFlags::Exact).unwrap(), 4);
+		assert_eq!(demuxer.read_packet().unwrap().map(|packet| packet.data[0]), Some(16));
+		assert_eq!(demuxer.seek(1000, SeekFlags::Any).unwrap(), 10);
+		assert!(demuxer.read_packet().unwrap().is_none());
+		assert_eq!(demuxer.seek(-5, SeekFlags::KeyframeBefore).unwrap(), 0);
+		assert_eq!(demuxer.read_packet().unwrap().map(|packet| packet.data.len()), Some(40));
+	}
 }
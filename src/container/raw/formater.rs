@@ -1,28 +1,53 @@
 use crate::codecs;
-use crate::core::frame::AudioFormat;
+use crate::core::frame::{AudioFormat, ChannelLayout};
+use crate::core::parameters::{AudioParameters, CodecParameters};
+use crate::core::stream::Stream;
+use crate::io;
 
 #[derive(Debug, Clone, Copy)]
 pub struct RawPcmFormat {
 	pub channels: u8,
 	pub sample_rate: u32,
 	pub bit_depth: u16,
+	// pcm or one of the G.711 laws, which have 8 bit samples
+	pub codec: &'static str,
 }
 
 impl Default for RawPcmFormat {
 	fn default() -> Self {
 		// default is pcm_16, stereo, 44.1kHz
-		Self { channels: 2, sample_rate: 44100, bit_depth: 16 }
+		Self { channels: 2, sample_rate: 44100, bit_depth: 16, codec: codecs::audio::PCM_S16LE }
 	}
 }
 
 impl RawPcmFormat {
 	pub fn new_for_codec(codec: &str) -> Result<Self, String> {
-		match codec {
-			codecs::audio::PCM_S16LE => Ok(Self::default()),
-			codecs::audio::PCM_S24LE => Ok(Self { bit_depth: 24, ..Self::default() }),
-			codecs::audio::PCM_F32LE => Ok(Self { bit_depth: 32, ..Self::default() }),
-			_ => Err(format!("raw codec '{}' is not supported", codec)),
-		}
+		let (codec, bit_depth) = match codec {
+			codecs::audio::PCM_S16LE => (codecs::audio::PCM_S16LE, 16),
+			codecs::audio::PCM_S24LE => (codecs::audio::PCM_S24LE, 24),
+			codecs::audio::PCM_F32LE => (codecs::audio::PCM_F32LE, 32),
+			codecs::audio::PCM_MULAW => (codecs::audio::PCM_MULAW, 8),
+			codecs::audio::PCM_ALAW => (codecs::audio::PCM_ALAW, 8),
+			_ => return Err(format!("raw codec '{}' is not supported", codec)),
+		};
+		Ok(Self { bit_depth, codec, ..Self::default() })
+	}
+
+	// bit depth follows the codec, rate and channels the audio parameters
+	pub fn from_stream(stream: &Stream) -> io::Result<Self> {
+		let mut format = Self::new_for_codec(&stream.codec).map_err(io::Error::invalid_data)?;
+		let Some(audio) = stream.audio_parameters() else {
+			return Err(io::Error::invalid_data(format!("{} is not an audio stream", stream)));
+		};
+		format.sample_rate = audio.sample_rate;
+		format.channels = audio.channels();
+		Ok(format)
+	}
+
+	pub fn parameters(&self) -> CodecParameters {
+		let layout = ChannelLayout::default_for(self.channels);
+		let audio = AudioParameters::new(self.sample_rate, layout, self.bit_depth);
+		CodecParameters::Audio(audio.with_block_align(self.block_align()))
 	}
 
 	pub fn bytes_per_sample(&self) -> usize {
@@ -51,21 +76,13 @@ impl RawPcmFormat {
 	}
 
 	pub fn to_codec_string(&self) -> &'static str {
-		match self.bit_depth {
-			16 => codecs::audio::PCM_S16LE,
-			24 => codecs::audio::PCM_S24LE,
-			32 => codecs::audio::PCM_F32LE,
-			_ => codecs::audio::PCM_S16LE,
-		}
+		self.codec
 	}
 
 	pub fn apply_codec(&mut self, codec: &str) -> Result<(), String> {
-		match codec {
-			codecs::audio::PCM_S16LE => self.bit_depth = 16,
-			codecs::audio::PCM_S24LE => self.bit_depth = 24,
-			codecs::audio::PCM_F32LE => self.bit_depth = 32,
-			_ => return Err(format!("raw codec '{}' is not supported", codec)),
-		}
+		let format = Self::new_for_codec(codec)?;
+		self.bit_depth = format.bit_depth;
+		self.codec = format.codec;
 		Ok(())
 	}
 }
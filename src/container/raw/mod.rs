@@ -5,3 +5,81 @@ pub mod formater;
 pub use demuxer::RawPcmDemuxer;
 pub use muxer::RawPcmMuxer;
 pub use formater::RawPcmFormat;
+
+use crate::codecs::audio::{PCM_ALAW, PCM_F32LE, PCM_MULAW, PCM_S16LE, PCM_S24LE};
+use crate::container;
+use crate::core::registry::{ContainerEntry, DemuxerOptions, Registry};
+use crate::core::stream::Stream;
+use crate::core::{Demuxer, Muxer, SeekFlags};
+use crate::io::stdio::{StdioSink, StdioSource};
+use crate::io::{Error, ErrorKind, Result};
+
+// headerless, so there is nothing to probe and the input options say what it holds
+pub fn register(registry: &mut Registry) {
+	let entry = ContainerEntry::new(container::RAW)
+		.with_extensions(&[container::PCM])
+		.with_codecs(&[PCM_S16LE, PCM_S24LE, PCM_F32LE, PCM_MULAW, PCM_ALAW])
+		.with_demuxer(open_demuxer)
+		.with_muxer(create_muxer);
+	registry.register_container(entry);
+}
+
+fn open_demuxer(source: StdioSource, options: &DemuxerOptions) -> Result<Box<dyn Demuxer>> {
+	let (start, end) = options.sample_range.unwrap_or((0, None));
+	let demuxer = RawPcmDemuxer::new(source, input_format(options)?)?;
+	let mut demuxer = demuxer.with_sample_range(0, end)?;
+	match demuxer.seek(start as i64, SeekFlags::Exact) {
+		Ok(_) => Ok(Box::new(demuxer)),
+		// pipes read through the data before the range instead
+		Err(error) if matches!(error.kind(), ErrorKind::NotSeekable) => {
+			Ok(Box::new(demuxer.with_sample_range(start, end)?))
+		}
+		Err(error) => Err(error),
+	}
+}
+
+// nothing is assumed, reading a different layout than the one written gives noise
+fn input_format(options: &DemuxerOptions) -> Result<RawPcmFormat> {
+	let (Some(codec), Some(sample_rate), Some(layout)) =
+		(&options.codec, options.sample_rate, options.layout)
+	else {
+		let message = "raw input needs --input-audio codec=<codec> sample_rate=<rate> channels=<n>";
+		return Err(Error::invalid_data(message));
+	};
+	let mut format = RawPcmFormat::new_for_codec(codec).map_err(Error::invalid_data)?;
+	format.sample_rate = sample_rate;
+	format.channels = layout.channels();
+	Ok(format)
+}
+
+// holds exactly one audio stream
+fn create_muxer(sink: StdioSink, streams: &[Stream]) -> Result<Box<dyn Muxer>> {
+	let [stream] = streams else {
+		return Err(Error::invalid_data("raw holds exactly one stream"));
+	};
+	if !stream.is_audio() {
+		return Err(Error::invalid_data("raw holds audio only"));
+	}
+	Ok(Box::new(RawPcmMuxer::new(sink, RawPcmFormat::from_stream(stream)?)?))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::ChannelLayout;
+
+	#[test]
+	fn test_input_needs_its_layout() {
+		let mut options = DemuxerOptions { sample_rate: Some(48000), ..Default::default() };
+		options.layout = Some(ChannelLayout::MONO);
+		assert!(input_format(&options).is_err());
+
+		options.codec = Some(PCM_F32LE.to_string());
+		let format = input_format(&options).unwrap();
+		assert_eq!((format.codec, format.sample_rate, format.channels), (PCM_F32LE, 48000, 1));
+		assert_eq!(format.bit_depth, 32);
+
+		options.codec = Some("opus".to_string());
+		assert!(input_format(&options).is_err());
+	}
+}
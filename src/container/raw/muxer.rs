@@ -18,7 +18,8 @@ impl<W: MediaWrite + MediaSeek> RawPcmMuxer<W> {
 		let codec_name = format.to_codec_string().to_string();
 		let time = Time::new(1, format.sample_rate);
 		let mut streams = stream::Streams::new_empty();
-		let stream = Stream::new(0, 0, StreamKind::Audio, codec_name, time);
+		let stream =
+			Stream::new(0, 0, StreamKind::Audio, codec_name, time).with_parameters(format.parameters());
 
 		streams.add(stream);
 
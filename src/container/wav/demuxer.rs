@@ -1,31 +1,55 @@
-use super::header::WavHeader;
-use super::{WavFormat, WavMetadata};
+use super::WavFormat;
+use super::header::{WAVE_FORMAT_EXTENSIBLE, WavHeader, sub_format_code};
+use super::tags;
+use crate::core::metadata::{Broadcast, Metadata, SampleLoop, Sampler};
 use crate::core::packet::Packet;
-use crate::core::{Demuxer, stream, time};
-use crate::io::{Error, MediaRead, ReadPrimitives, Result};
+use crate::core::{Demuxer, SeekFlags, stream, time};
+use crate::io::{Error, ErrorKind, MediaRead, MediaSeek, ReadPrimitives, Result, SeekFrom};
 
 pub struct WavDemuxer<R: MediaRead> {
 	reader: R,
 	format: WavFormat,
 	streams: stream::Streams,
-	metadata: WavMetadata,
+	metadata: Metadata,
+	data_size: u64,
 	data_remaining: u64,
 	packet_count: u64,
 	sample_position: u64,
+	end_position: Option<u64>,
 }
 
 impl<R: MediaRead> WavDemuxer<R> {
 	const CHUNK_SIZE_LIMIT: usize = 65536;
+	const UNKNOWN_SIZE: u64 = u32::MAX as u64;
+	// bext up to the coding history
+	const BEXT_SIZE: u64 = 602;
 
 	pub fn new(mut reader: R) -> Result<Self> {
-		let (header, metadata, data_size) = Self::read_wav_and_find_data(&mut reader)?;
+		let (header, metadata, data_size, fact) = Self::read_wav_and_find_data(&mut reader)?;
 		header.validate()?;
 
 		let format = header.to_format();
 
 		let codec_name = format.to_codec_string().to_string();
 		let time = time::Time::new(1, header.sample_rate);
-		let stream = stream::Stream::new(0, 0, stream::StreamKind::Audio, codec_name, time);
+		let mut stream = stream::Stream::new(0, 0, stream::StreamKind::Audio, codec_name, time)
+			.with_parameters(format.parameters())
+			.with_codec_private(header.extension);
+
+		// a streamed file leaves the size unset, its data runs to the end of input,
+		// compressed data pads its last block and gives the real length in the fact chunk
+		let data_size = match data_size {
+			Self::UNKNOWN_SIZE => u64::MAX,
+			size => {
+				let blocks = size.div_ceil(std::cmp::max(1, format.block_align() as u64));
+				let duration = match fact {
+					Some(samples) if format.is_compressed() => samples,
+					_ => blocks * format.block_samples() as u64,
+				};
+				stream = stream.with_duration(duration as i64);
+				size
+			}
+		};
 		let streams = stream::Streams::new(vec![stream]);
 
 		Ok(Self {
@@ -33,16 +57,42 @@ impl<R: MediaRead> WavDemuxer<R> {
 			format,
 			streams,
 			metadata,
+			data_size,
 			data_remaining: data_size,
 			packet_count: 0,
 			sample_position: 0,
+			end_position: None,
 		})
 	}
 
-	fn read_wav_and_find_data(reader: &mut R) -> Result<(WavHeader, WavMetadata, u64)> {
-		Self::check_fourcc(reader, "RIFF")?;
+	// drops the data before `start` without reading it into packets and stops at `end`
+	// block codecs start at the block holding `start`
+	pub fn with_sample_range(mut self, start: u64, end: Option<u64>) -> Result<Self> {
+		let block_align = self.format.block_align() as u64;
+		let skip = std::cmp::min(self.data_remaining / block_align, start / self.block_samples());
+		Self::skip_bytes(&mut self.reader, skip * block_align)?;
+
+		self.data_remaining -= skip * block_align;
+		self.sample_position = skip * self.block_samples();
+		self.end_position = end;
+		Ok(self)
+	}
+
+	fn block_samples(&self) -> u64 {
+		self.format.block_samples() as u64
+	}
+
+	// header, tags, data size and the sample count of a fact chunk
+	fn read_wav_and_find_data(reader: &mut R) -> Result<(WavHeader, Metadata, u64, Option<u64>)> {
+		let riff = Self::read_fourcc(reader)?;
+		if !matches!(riff.as_str(), "RIFF" | "RF64" | "BW64") {
+			return Err(Error::invalid_data(format!("expected RIFF, found {}", riff)));
+		}
 		let _file_size = reader.read_u32_le()?;
 		Self::check_fourcc(reader, "WAVE")?;
+		// 64 bit files give sizes that do not fit here as 0xFFFFFFFF and the real ones in ds64
+		let mut ds64_data_size = None;
+		let mut fact = None;
 
 		let mut header = WavHeader {
 			channels: 0,
@@ -51,8 +101,12 @@ impl<R: MediaRead> WavDemuxer<R> {
 			block_align: 0,
 			bits_per_sample: 0,
 			format_code: 0,
+			valid_bits: 0,
+			channel_mask: 0,
+			samples_per_block: 0,
+			extension: Vec::new(),
 		};
-		let mut metadata = WavMetadata::new();
+		let mut metadata = Metadata::new();
 
 		loop {
 			let chunk_id = Self::read_fourcc(reader)?;
@@ -60,11 +114,44 @@ impl<R: MediaRead> WavDemuxer<R> {
 
 			match chunk_id.as_str() {
 				"fmt " => Self::read_fmt_chunk(reader, chunk_size, &mut header)?,
-				"LIST" => Self::read_list_chunk(reader, chunk_size, &mut metadata)?,
-				"data" => return Ok((header, metadata, chunk_size)),
-				_ => Self::skip_bytes(reader, chunk_size)?,
+				"ds64" => ds64_data_size = Some(Self::read_ds64_chunk(reader, chunk_size)?),
+				"fact" if chunk_size >= 4 => {
+					fact = Some(reader.read_u32_le()? as u64);
+					Self::skip_bytes(reader, chunk_size - 4)?;
+				}
+				"data" => match ds64_data_size {
+					Some(size) if chunk_size == Self::UNKNOWN_SIZE => {
+						return Ok((header, metadata, size, fact));
+					}
+					_ => return Ok((header, metadata, chunk_size, fact)),
+				},
+				id => Self::read_metadata_chunk(reader, id, chunk_size, &mut metadata)?,
+			}
+		}
+	}
+
+	// chunks that may sit on either side of the data, anything unknown or malformed is skipped
+	fn read_metadata_chunk(
+		reader: &mut R,
+		id: &str,
+		chunk_size: u64,
+		metadata: &mut Metadata,
+	) -> Result<()> {
+		match id {
+			"LIST" => Self::read_list_chunk(reader, chunk_size, metadata)?,
+			"bext" if chunk_size >= Self::BEXT_SIZE => {
+				metadata.broadcast = Some(Self::read_bext_chunk(reader, chunk_size)?)
 			}
+			"id3 " | "ID3 " => tags::read_id3(&Self::read_bytes(reader, chunk_size)?, metadata),
+			"iXML" => metadata.ixml = Some(Self::read_string(reader, chunk_size)?),
+			"cue " => Self::read_cue_chunk(reader, chunk_size, metadata)?,
+			"smpl" if chunk_size >= 36 => metadata.sampler = Self::read_smpl_chunk(reader, chunk_size)?,
+			_ => Self::skip_bytes(reader, chunk_size)?,
 		}
+		if chunk_size % 2 == 1 {
+			reader.read_u8()?;
+		}
+		Ok(())
 	}
 
 	fn read_fmt_chunk(reader: &mut R, chunk_size: u64, header: &mut WavHeader) -> Result<()> {
@@ -79,19 +166,63 @@ impl<R: MediaRead> WavDemuxer<R> {
 		header.block_align = reader.read_u16_le()?;
 		header.bits_per_sample = reader.read_u16_le()?;
 
-		let remaining = chunk_size - 16;
+		let mut remaining = chunk_size - 16;
+		if header.format_code == WAVE_FORMAT_EXTENSIBLE {
+			if remaining < 24 {
+				return Err(Error::invalid_data("extensible fmt chunk too small"));
+			}
+			let _extension_size = reader.read_u16_le()?;
+			header.valid_bits = reader.read_u16_le()?;
+			header.channel_mask = reader.read_u32_le()?;
+			let mut guid = [0u8; 16];
+			reader.read_exact(&mut guid)?;
+			header.format_code = sub_format_code(&guid)?;
+			remaining -= 24;
+		} else if remaining >= 2 {
+			let extension_size = std::cmp::min(reader.read_u16_le()? as u64, remaining - 2);
+			header.extension = Self::read_bytes(reader, extension_size)?;
+			remaining -= 2 + extension_size;
+		}
+		// adpcm opens its extension with the samples per block
+		let format = header.to_format();
+		if format.is_adpcm() {
+			header.samples_per_block = match header.extension.as_slice() {
+				[low, high, ..] => u16::from_le_bytes([*low, *high]),
+				_ => 0,
+			};
+		}
+		// some writers leave it out, a full block is assumed then
+		if format.is_adpcm() && header.samples_per_block == 0 {
+			header.samples_per_block = format.max_samples_per_block();
+		}
+
 		if remaining > 0 {
 			Self::skip_bytes(reader, remaining)?;
 		}
 		Ok(())
 	}
 
-	fn read_list_chunk(reader: &mut R, chunk_size: u64, metadata: &mut WavMetadata) -> Result<()> {
+	// riff size, data size and sample count, the table for other large chunks is skipped
+	fn read_ds64_chunk(reader: &mut R, chunk_size: u64) -> Result<u64> {
+		if chunk_size < 24 {
+			return Err(Error::invalid_data("ds64 chunk too small"));
+		}
+		let _riff_size = reader.read_u64_le()?;
+		let data_size = reader.read_u64_le()?;
+		let _sample_count = reader.read_u64_le()?;
+		Self::skip_bytes(reader, chunk_size - 24)?;
+		Ok(data_size)
+	}
+
+	fn read_list_chunk(reader: &mut R, chunk_size: u64, metadata: &mut Metadata) -> Result<()> {
 		if chunk_size < 4 {
-			return Ok(());
+			return Self::skip_bytes(reader, chunk_size);
 		}
 
 		let form_type = Self::read_fourcc(reader)?;
+		if form_type == "adtl" {
+			return Self::read_adtl_list(reader, chunk_size - 4, metadata);
+		}
 		if form_type != "INFO" {
 			return Self::skip_bytes(reader, chunk_size - 4);
 		}
@@ -101,29 +232,138 @@ impl<R: MediaRead> WavDemuxer<R> {
 			let id = Self::read_fourcc(reader)?;
 			let size = reader.read_u32_le()? as u64;
 			position += 8;
+			// an entry running past the list ends it
+			if position + size > chunk_size {
+				break;
+			}
 
 			let data = Self::read_bytes(reader, size)?;
 			position += size;
 
 			let value = String::from_utf8_lossy(&data).trim_end_matches('\0').to_string();
 
-			match id.as_str() {
-				"IART" => metadata.set("artist", value),
-				"INAM" => metadata.set("title", value),
-				"ICOM" => metadata.set("comment", value),
-				"ICOP" => metadata.set("copyright", value),
-				"ISFT" => metadata.set("software", value),
-				"IGNR" => metadata.set("genre", value),
-				"ITRK" => metadata.set("track", value),
-				_ => {}
+			if !value.is_empty() {
+				metadata.set(&tags::info_key(&id), value);
 			}
 
-			if size % 2 == 1 {
+			if size % 2 == 1 && position < chunk_size {
 				reader.read_u8()?;
 				position += 1;
 			}
 		}
-		Ok(())
+		Self::skip_bytes(reader, chunk_size - position)
+	}
+
+	// cue point labels, notes and text spans are skipped
+	fn read_adtl_list(reader: &mut R, list_size: u64, metadata: &mut Metadata) -> Result<()> {
+		let mut position = 0u64;
+		while position + 8 <= list_size {
+			let id = Self::read_fourcc(reader)?;
+			let size = reader.read_u32_le()? as u64;
+			let padded = std::cmp::min(size + size % 2, list_size - position - 8);
+			position += 8 + padded;
+
+			if id == "labl" && padded >= 4 {
+				let cue_id = reader.read_u32_le()?;
+				metadata.marker_mut(cue_id).label = Some(Self::read_string(reader, padded - 4)?);
+			} else {
+				Self::skip_bytes(reader, padded)?;
+			}
+		}
+		Self::skip_bytes(reader, list_size.saturating_sub(position))
+	}
+
+	fn read_bext_chunk(reader: &mut R, chunk_size: u64) -> Result<Broadcast> {
+		let description = Self::read_string(reader, 256)?;
+		let originator = Self::read_string(reader, 32)?;
+		let originator_reference = Self::read_string(reader, 32)?;
+		let origination_date = Self::read_string(reader, 10)?;
+		let origination_time = Self::read_string(reader, 8)?;
+		let time_reference = reader.read_u64_le()?;
+		let version = reader.read_u16_le()?;
+		let umid = Self::read_bytes(reader, 64)?;
+		let loudness_value = reader.read_u16_le()? as i16;
+		let loudness_range = reader.read_u16_le()? as i16;
+		let max_true_peak_level = reader.read_u16_le()? as i16;
+		let max_momentary_loudness = reader.read_u16_le()? as i16;
+		let max_short_term_loudness = reader.read_u16_le()? as i16;
+		Self::skip_bytes(reader, 180)?;
+		let coding_history = Self::read_string(reader, chunk_size - Self::BEXT_SIZE)?;
+
+		Ok(Broadcast {
+			description,
+			originator,
+			originator_reference,
+			origination_date,
+			origination_time,
+			time_reference,
+			version,
+			umid,
+			loudness_value,
+			loudness_range,
+			max_true_peak_level,
+			max_momentary_loudness,
+			max_short_term_loudness,
+			coding_history,
+		})
+	}
+
+	// only the sample offset is kept, cue points always refer to the single data chunk,
+	// a count the chunk cannot hold drops all of them
+	fn read_cue_chunk(reader: &mut R, chunk_size: u64, metadata: &mut Metadata) -> Result<()> {
+		if chunk_size < 4 {
+			return Self::skip_bytes(reader, chunk_size);
+		}
+		let count = reader.read_u32_le()? as u64;
+		if 4 + count * 24 > chunk_size {
+			return Self::skip_bytes(reader, chunk_size - 4);
+		}
+		for _ in 0..count {
+			let id = reader.read_u32_le()?;
+			let _position = reader.read_u32_le()?;
+			let _chunk_id = Self::read_fourcc(reader)?;
+			let _chunk_start = reader.read_u32_le()?;
+			let _block_start = reader.read_u32_le()?;
+			metadata.marker_mut(id).position = reader.read_u32_le()? as u64;
+		}
+		Self::skip_bytes(reader, chunk_size - 4 - count * 24)
+	}
+
+	// None when the loop count does not fit the chunk
+	fn read_smpl_chunk(reader: &mut R, chunk_size: u64) -> Result<Option<Sampler>> {
+		let mut sampler = Sampler {
+			manufacturer: reader.read_u32_le()?,
+			product: reader.read_u32_le()?,
+			sample_period: reader.read_u32_le()?,
+			midi_unity_note: reader.read_u32_le()?,
+			midi_pitch_fraction: reader.read_u32_le()?,
+			smpte_format: reader.read_u32_le()?,
+			smpte_offset: reader.read_u32_le()?,
+			loops: Vec::new(),
+		};
+		let count = reader.read_u32_le()? as u64;
+		let _sampler_data = reader.read_u32_le()?;
+		if 36 + count * 24 > chunk_size {
+			Self::skip_bytes(reader, chunk_size - 36)?;
+			return Ok(None);
+		}
+		for _ in 0..count {
+			sampler.loops.push(SampleLoop {
+				id: reader.read_u32_le()?,
+				kind: reader.read_u32_le()?,
+				start: reader.read_u32_le()? as u64,
+				end: reader.read_u32_le()? as u64,
+				fraction: reader.read_u32_le()?,
+				play_count: reader.read_u32_le()?,
+			});
+		}
+		Self::skip_bytes(reader, chunk_size - 36 - count * 24)?;
+		Ok(Some(sampler))
+	}
+
+	fn read_string(reader: &mut R, size: u64) -> Result<String> {
+		let data = Self::read_bytes(reader, size)?;
+		Ok(String::from_utf8_lossy(&data).trim_end_matches('\0').to_string())
 	}
 
 	fn read_fourcc(reader: &mut R) -> Result<String> {
@@ -135,20 +375,34 @@ impl<R: MediaRead> WavDemuxer<R> {
 	fn check_fourcc(reader: &mut R, expected: &str) -> Result<()> {
 		let actual = Self::read_fourcc(reader)?;
 		if actual != expected {
-			return Err(Error::invalid_data(&format!("expected {}, found {}", expected, actual)));
+			return Err(Error::invalid_data(format!("expected {}, found {}", expected, actual)));
 		}
 		Ok(())
 	}
 
+	// grows with what is actually read, a bogus size runs into the end of input
+	// instead of being allocated up front
 	fn read_bytes(reader: &mut R, size: u64) -> Result<Vec<u8>> {
-		let mut buf = vec![0u8; size as usize];
-		reader.read_exact(&mut buf)?;
+		let mut buf = Vec::new();
+		let mut remaining = size;
+		while remaining > 0 {
+			let start = buf.len();
+			let length = std::cmp::min(remaining, Self::CHUNK_SIZE_LIMIT as u64) as usize;
+			buf.resize(start + length, 0);
+			reader.read_exact(&mut buf[start..])?;
+			remaining -= length as u64;
+		}
 		Ok(buf)
 	}
 
 	fn skip_bytes(reader: &mut R, size: u64) -> Result<()> {
-		let mut buf = vec![0u8; size as usize];
-		reader.read_exact(&mut buf)?;
+		let mut buf = vec![0u8; std::cmp::min(size, Self::CHUNK_SIZE_LIMIT as u64) as usize];
+		let mut remaining = size;
+		while remaining > 0 {
+			let length = std::cmp::min(remaining, buf.len() as u64) as usize;
+			reader.read_exact(&mut buf[..length])?;
+			remaining -= length as u64;
+		}
 		Ok(())
 	}
 
@@ -157,11 +411,27 @@ impl<R: MediaRead> WavDemuxer<R> {
 			return Ok(None);
 		}
 
+		// a block codec gets one block per packet
 		let block_align = self.format.block_align() as u64;
-		let max_chunk = (Self::CHUNK_SIZE_LIMIT as u64 / block_align) * block_align;
-		let chunk_size = std::cmp::min(self.data_remaining, max_chunk) as usize;
+		let max_chunk = match self.block_samples() {
+			1 => (Self::CHUNK_SIZE_LIMIT as u64 / block_align) * block_align,
+			_ => block_align,
+		};
+		let mut chunk_size = std::cmp::min(self.data_remaining, max_chunk);
+		if let Some(end) = self.end_position {
+			let left = end.saturating_sub(self.sample_position).div_ceil(self.block_samples());
+			chunk_size = std::cmp::min(chunk_size, left * block_align);
+		}
+		if chunk_size == 0 {
+			return Ok(None);
+		}
+		let chunk_size = chunk_size as usize;
 		let mut data = vec![0u8; chunk_size];
-		let bytes_read = self.reader.read(&mut data)?;
+		// pcm keeps whole sample frames, the last block of a block codec may come short
+		let bytes_read = match self.block_samples() {
+			1 => self.reader.read_to_fill(&mut data)? / block_align as usize * block_align as usize,
+			_ => self.reader.read_to_fill(&mut data)?,
+		};
 
 		if bytes_read == 0 {
 			return Ok(None);
@@ -171,9 +441,10 @@ impl<R: MediaRead> WavDemuxer<R> {
 		self.data_remaining -= bytes_read as u64;
 
 		let time = time::Time::new(1, self.format.sample_rate);
-		let packet = Packet::new(data, 0, time).with_pts(self.sample_position as i64);
+		let position = self.sample_position as i64;
+		let packet = Packet::new(data, 0, time).with_pts(position).with_dts(position);
 
-		self.sample_position += (bytes_read / self.format.bytes_per_frame()) as u64;
+		self.sample_position += (bytes_read as u64).div_ceil(block_align) * self.block_samples();
 		self.packet_count += 1;
 
 		Ok(Some(packet))
@@ -186,16 +457,245 @@ impl<R: MediaRead> WavDemuxer<R> {
 	pub fn format(&self) -> WavFormat {
 		self.format
 	}
-	pub fn metadata(&self) -> &WavMetadata {
+	pub fn metadata(&self) -> &Metadata {
 		&self.metadata
 	}
 }
 
-impl<R: MediaRead> Demuxer for WavDemuxer<R> {
+impl<R: MediaRead + MediaSeek> WavDemuxer<R> {
+	// editors often put cue points and tags after the data, they are read before the first packet
+	pub fn read_trailing_chunks(&mut self) -> Result<()> {
+		if self.data_size == u64::MAX {
+			return Ok(());
+		}
+		let resume = self.reader.stream_position()?;
+		let end = self.reader.seek(SeekFrom::End(0))?;
+		self.reader.seek(SeekFrom::Start(resume))?;
+		self.reader.seek(SeekFrom::Current((self.data_remaining + self.data_size % 2) as i64))?;
+
+		loop {
+			let chunk = Self::read_fourcc(&mut self.reader).and_then(|id| {
+				let size = self.reader.read_u32_le()? as u64;
+				// a size past the end of the file is garbage, not a chunk to read
+				if self.reader.stream_position()? + size > end {
+					return Err(Error::invalid_data(format!("{} chunk runs past the end", id)));
+				}
+				Self::read_metadata_chunk(&mut self.reader, &id, size, &mut self.metadata)
+			});
+			match chunk {
+				Ok(()) => {}
+				// a cut off or malformed chunk ends the list, the data before it is still good
+				Err(error) if matches!(error.kind(), ErrorKind::UnexpectedEof | ErrorKind::InvalidData) => {
+					break;
+				}
+				Err(error) => return Err(error),
+			}
+		}
+		self.reader.seek(SeekFrom::Start(resume))?;
+		Ok(())
+	}
+
+	// moves within the data chunk to a block boundary, which is every sample for pcm
+	pub fn seek(&mut self, sample: i64, flags: SeekFlags) -> Result<i64> {
+		let block_align = self.format.block_align() as u64;
+		let block_samples = self.block_samples();
+		let total_blocks = self.data_size / block_align;
+
+		let sample = std::cmp::max(0, sample) as u64;
+		let block = match flags {
+			SeekFlags::Any => (sample + block_samples / 2) / block_samples,
+			SeekFlags::KeyframeBefore | SeekFlags::Exact => sample / block_samples,
+		};
+		let block = std::cmp::min(block, total_blocks);
+
+		let current = self.data_size - self.data_remaining;
+		self.reader.seek(SeekFrom::Current((block * block_align) as i64 - current as i64))?;
+		self.data_remaining = self.data_size - block * block_align;
+		self.sample_position = block * block_samples;
+		Ok(self.sample_position as i64)
+	}
+}
+
+impl<R: MediaRead + MediaSeek> Demuxer for WavDemuxer<R> {
 	fn streams(&self) -> &stream::Streams {
 		&self.streams
 	}
 	fn read_packet(&mut self) -> Result<Option<Packet>> {
 		self.read_packet()
 	}
+	fn metadata(&self) -> Option<&Metadata> {
+		Some(&self.metadata)
+	}
+	fn seek(&mut self, stream_index: usize, timestamp: i64, flags: SeekFlags) -> Result<i64> {
+		if stream_index != 0 {
+			return Err(Error::invalid_data(format!("stream {} does not exist", stream_index)));
+		}
+		self.seek(timestamp, flags)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, sample};
+	use crate::io::Cursor;
+
+	fn riff(fmt: &[u8], data: &[u8]) -> Vec<u8> {
+		let mut bytes = b"RIFF".to_vec();
+		bytes.extend((20 + fmt.len() as u32 + data.len() as u32).to_le_bytes());
+		bytes.extend(b"WAVEfmt ");
+		bytes.extend((fmt.len() as u32).to_le_bytes());
+		bytes.extend(fmt);
+		bytes.extend(b"data");
+		bytes.extend((data.len() as u32).to_le_bytes());
+		bytes.extend(data);
+		bytes
+	}
+
+	// mono 16 bit at 1 kHz, every sample holds its own index
+	fn wav(samples: u16) -> Vec<u8> {
+		let fmt = [1, 0, 1, 0, 0xE8, 0x03, 0, 0, 0xD0, 0x07, 0, 0, 2, 0, 16, 0];
+		let data: Vec<u8> = (0..samples).flat_map(|n| n.to_le_bytes()).collect();
+		riff(&fmt, &data)
+	}
+
+	fn first_sample(packet: &Packet) -> u16 {
+		u16::from_le_bytes([packet.data[0], packet.data[1]])
+	}
+
+	#[test]
+	fn test_seek_lands_on_the_sample() {
+		let mut demuxer = WavDemuxer::new(Cursor::new(wav(3000))).unwrap();
+		demuxer.read_packet().unwrap();
+
+		assert_eq!(Demuxer::seek(&mut demuxer, 0, 1234, SeekFlags::Exact).unwrap(), 1234);
+		let packet = demuxer.read_packet().unwrap().unwrap();
+		assert_eq!((packet.pts, first_sample(&packet)), (1234, 1234));
+
+		assert_eq!(Demuxer::seek(&mut demuxer, 0, 10, SeekFlags::KeyframeBefore).unwrap(), 10);
+		assert_eq!(first_sample(&demuxer.read_packet().unwrap().unwrap()), 10);
+
+		assert_eq!(Demuxer::seek(&mut demuxer, 0, 5000, SeekFlags::Any).unwrap(), 3000);
+		assert!(demuxer.read_packet().unwrap().is_none());
+		assert!(Demuxer::seek(&mut demuxer, 1, 0, SeekFlags::Any).is_err());
+	}
+
+	#[test]
+	fn test_extensible_fmt() {
+		// 2.1 at 24 bit, 20 of them valid
+		let mut fmt = vec![0xFE, 0xFF, 3, 0, 0x44, 0xAC, 0, 0];
+		fmt.extend((44100u32 * 9).to_le_bytes());
+		fmt.extend([9, 0, 24, 0, 22, 0, 20, 0, 0x0B, 0, 0, 0]);
+		fmt.extend(crate::container::wav::header::sub_format_guid(1));
+		let demuxer = WavDemuxer::new(Cursor::new(riff(&fmt, &[0; 18]))).unwrap();
+
+		let stream = demuxer.streams().get(0).unwrap();
+		assert_eq!(stream.codec, crate::codecs::audio::PCM_S24LE);
+		let audio = stream.audio_parameters().unwrap();
+		assert_eq!(audio.layout, crate::core::frame::ChannelLayout::SURROUND_2_1);
+
+		// not a KSDATAFORMAT_SUBTYPE guid
+		fmt[30] = 0x55;
+		assert!(WavDemuxer::new(Cursor::new(riff(&fmt, &[0; 18]))).is_err());
+	}
+
+	#[test]
+	fn test_extensible_int32() {
+		// stereo 32 bit integer, the way DAWs export it
+		let mut fmt = vec![0xFE, 0xFF, 2, 0, 0x44, 0xAC, 0, 0];
+		fmt.extend((44100u32 * 8).to_le_bytes());
+		fmt.extend([8, 0, 32, 0, 22, 0, 32, 0, 3, 0, 0, 0]);
+		fmt.extend(crate::container::wav::header::sub_format_guid(1));
+		let values = [i32::MIN, i32::MAX, 1 << 30, -(1 << 30)];
+		let data: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+
+		let mut demuxer = WavDemuxer::new(Cursor::new(riff(&fmt, &data))).unwrap();
+		let stream = demuxer.streams().get(0).unwrap().clone();
+		assert_eq!(stream.codec, crate::codecs::audio::PCM_S32LE);
+		let format = WavFormat::from_stream(&stream).unwrap();
+		assert_eq!(format.audio_format(), AudioFormat::PCM32Int);
+		let packet = demuxer.read_packet().unwrap().unwrap();
+		let samples = sample::to_f32(&packet.data, format.audio_format()).unwrap();
+		assert_eq!(samples, [-1.0, 1.0, 0.5, -0.5]);
+
+		// the same samples as float are not taken for integers
+		fmt[24..26].copy_from_slice(&3u16.to_le_bytes());
+		let demuxer = WavDemuxer::new(Cursor::new(riff(&fmt, &data))).unwrap();
+		assert_eq!(demuxer.streams().get(0).unwrap().codec, crate::codecs::audio::PCM_F32LE);
+
+		// 8 bit pcm is not copied out under a 16 bit fmt
+		let fmt = [1, 0, 1, 0, 0x40, 0x1F, 0, 0, 0x40, 0x1F, 0, 0, 1, 0, 8, 0];
+		assert!(WavDemuxer::new(Cursor::new(riff(&fmt, &[0x80; 8]))).is_err());
+	}
+
+	#[test]
+	fn test_ima_adpcm_blocks_and_fact() {
+		// mono 8 kHz in 256 byte blocks of 505 samples, the fact chunk cuts the last one short
+		let mut fmt = vec![0x11, 0, 1, 0, 0x40, 0x1F, 0, 0, 0xD7, 0x0F, 0, 0, 0, 1, 4, 0, 2, 0];
+		fmt.extend(505u16.to_le_bytes());
+		let mut bytes = riff(&fmt, &[0; 512]);
+		let mut fact = b"fact".to_vec();
+		fact.extend(4u32.to_le_bytes());
+		fact.extend(700u32.to_le_bytes());
+		bytes.splice(40..40, fact);
+
+		let mut demuxer = WavDemuxer::new(Cursor::new(bytes)).unwrap();
+		let stream = demuxer.streams().get(0).unwrap();
+		assert_eq!(stream.codec, crate::codecs::audio::ADPCM_IMA_WAV);
+		assert_eq!(stream.duration, Some(700));
+		assert_eq!(stream.audio_parameters().map(|audio| audio.frame_size), Some(505));
+
+		assert_eq!(Demuxer::seek(&mut demuxer, 0, 600, SeekFlags::Exact).unwrap(), 505);
+		let packet = demuxer.read_packet().unwrap().unwrap();
+		assert_eq!((packet.pts, packet.data.len()), (505, 256));
+		assert!(demuxer.read_packet().unwrap().is_none());
+	}
+
+	#[test]
+	fn test_malformed_chunks_do_not_fail_the_open() {
+		let chunk = |id: &[u8], size: u32, body: &[u8]| {
+			let mut bytes = id.to_vec();
+			bytes.extend(size.to_le_bytes());
+			bytes.extend(body);
+			bytes
+		};
+		// a cue count the chunk cannot hold and a short bext ahead of the data
+		let mut bytes = wav(4);
+		let mut ahead = chunk(b"cue ", 4, &5u32.to_le_bytes());
+		ahead.extend(chunk(b"bext", 10, &[0; 10]));
+		bytes.splice(36..36, ahead);
+		let demuxer = WavDemuxer::new(Cursor::new(bytes.clone())).unwrap();
+		assert!(demuxer.metadata().markers.is_empty());
+		assert!(demuxer.metadata().broadcast.is_none());
+
+		// after the data, a short smpl and then an id3 chunk claiming 4 GiB
+		bytes.extend(chunk(b"smpl", 36, &[0xFF; 36]));
+		bytes.extend(chunk(b"id3 ", u32::MAX - 1, b"ID3"));
+		let mut demuxer = WavDemuxer::new(Cursor::new(bytes)).unwrap();
+		demuxer.read_trailing_chunks().unwrap();
+		assert!(demuxer.metadata().sampler.is_none());
+		assert_eq!(demuxer.read_packet().unwrap().map(|packet| packet.data.len()), Some(8));
+	}
+
+	#[test]
+	fn test_rf64_sizes_come_from_ds64() {
+		// 50 samples behind a data size left unset
+		let mut bytes = wav(50);
+		bytes[0..4].copy_from_slice(b"RF64");
+		let data_size = bytes.len() - 104;
+		bytes[data_size..data_size + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+		let mut ds64 = b"ds64".to_vec();
+		ds64.extend(28u32.to_le_bytes());
+		ds64.extend(0u64.to_le_bytes());
+		ds64.extend(100u64.to_le_bytes());
+		ds64.extend(50u64.to_le_bytes());
+		ds64.extend(0u32.to_le_bytes());
+		bytes.splice(12..12, ds64);
+
+		let mut demuxer = WavDemuxer::new(Cursor::new(bytes)).unwrap();
+		assert_eq!(demuxer.streams().get(0).and_then(|stream| stream.duration), Some(50));
+		assert_eq!(demuxer.read_packet().unwrap().map(|packet| packet.data.len()), Some(100));
+		assert!(demuxer.read_packet().unwrap().is_none());
+	}
 }
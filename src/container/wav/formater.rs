@@ -1,23 +1,40 @@
 use crate::codecs;
-use crate::container::raw;
 pub use crate::container::wav::demuxer::WavDemuxer;
-pub use crate::container::wav::metadata::WavMetadata;
 pub use crate::container::wav::muxer::WavMuxer;
 pub use crate::core;
-use crate::core::frame::AudioFormat;
+use crate::core::frame::{AudioFormat, ChannelLayout};
+use crate::core::parameters::{AudioParameters, CodecParameters};
+use crate::core::stream::Stream;
+use crate::io;
 
 #[derive(Debug, Clone, Copy)]
 pub struct WavFormat {
 	pub channels: u8,
 	pub sample_rate: u32,
 	pub bit_depth: u16,
+	// samples padded below the bit depth, 0 when every bit is valid
+	pub valid_bits: u16,
 	pub format_code: u16,
+	// speakers of an extensible fmt, 0 leaves them to the channel count
+	pub channel_mask: u32,
+	// block codecs only, 0 for pcm where a block is one sample per channel
+	pub block_size: u16,
+	pub samples_per_block: u16,
 }
 
 impl Default for WavFormat {
 	fn default() -> Self {
 		// defaut is pcm_16
-		Self { channels: 2, sample_rate: 44100, bit_depth: 16, format_code: 1 }
+		Self {
+			channels: 2,
+			sample_rate: 44100,
+			bit_depth: 16,
+			valid_bits: 0,
+			format_code: 1,
+			channel_mask: 0,
+			block_size: 0,
+			samples_per_block: 0,
+		}
 	}
 }
 
@@ -26,16 +43,99 @@ impl WavFormat {
 		match codec {
 			codecs::audio::PCM_S16LE => Ok(Self::default()),
 			codecs::audio::PCM_S24LE => Ok(Self { bit_depth: 24, ..Self::default() }),
+			codecs::audio::PCM_S32LE => Ok(Self { bit_depth: 32, ..Self::default() }),
 			codecs::audio::PCM_F32LE => Ok(Self { bit_depth: 32, format_code: 3, ..Self::default() }),
+			codecs::audio::PCM_ALAW => Ok(Self { bit_depth: 8, format_code: 6, ..Self::default() }),
+			codecs::audio::PCM_MULAW => Ok(Self { bit_depth: 8, format_code: 7, ..Self::default() }),
+			codecs::audio::ADPCM_IMA_WAV => {
+				Ok(Self { bit_depth: 4, format_code: 0x11, ..Self::default() })
+			}
+			codecs::audio::ADPCM_MS => Ok(Self { bit_depth: 4, format_code: 2, ..Self::default() }),
 			_ => Err(format!("wav codec '{}' is not supported", codec)),
 		}
 	}
 
-	pub fn to_raw_format(&self) -> raw::RawPcmFormat {
-		raw::RawPcmFormat {
-			channels: self.channels,
-			sample_rate: self.sample_rate,
-			bit_depth: self.bit_depth,
+	// bit depth follows the codec, rate and channels the audio parameters
+	pub fn from_stream(stream: &Stream) -> io::Result<Self> {
+		let mut format = Self::new_for_codec(&stream.codec).map_err(io::Error::invalid_data)?;
+		let Some(audio) = stream.audio_parameters() else {
+			return Err(io::Error::invalid_data(format!("{} is not an audio stream", stream)));
+		};
+		format.sample_rate = audio.sample_rate;
+		format.channels = audio.channels();
+		format.channel_mask = audio.layout.mask();
+		if audio.valid_bits < format.bit_depth {
+			format.valid_bits = audio.valid_bits;
+		}
+		if format.is_adpcm() {
+			format.with_adpcm_blocks(audio.block_align, audio.frame_size);
+		}
+		Ok(format)
+	}
+
+	// keeps a block layout that fits the channels, otherwise picks the size Windows uses,
+	// 256 bytes per channel at 11025 Hz and more as the rate goes up
+	fn with_adpcm_blocks(&mut self, block_size: u16, samples_per_block: u32) {
+		let channels = std::cmp::max(1, self.channels as u16);
+		let mut format = Self { block_size, ..*self };
+		if samples_per_block == 0 || format.max_samples_per_block() as u32 != samples_per_block {
+			let scale = std::cmp::max(1, self.sample_rate / 11025) as u16;
+			format.block_size = 256 * channels * std::cmp::min(scale, 8);
+		}
+		self.block_size = format.block_size;
+		self.samples_per_block = format.max_samples_per_block();
+	}
+
+	// samples per channel a full block holds, 0 when the block does not fit the channels
+	pub fn max_samples_per_block(&self) -> u16 {
+		let channels = std::cmp::max(1, self.channels as u16);
+		match (self.format_code, self.block_size) {
+			// a 4 byte header per channel holds the first sample, then 8 samples per 4 bytes
+			(0x11, size) if size >= 8 * channels && size % (4 * channels) == 0 => {
+				(size - 4 * channels) * 2 / channels + 1
+			}
+			// a 7 byte header per channel holds the first two samples, then 2 samples per byte
+			(2, size) if size >= 8 * channels => (size - 7 * channels) * 2 / channels + 2,
+			_ => 0,
+		}
+	}
+
+	pub fn is_adpcm(&self) -> bool {
+		matches!(self.format_code, 2 | 0x11)
+	}
+
+	// a mask that does not match the channel count is ignored
+	pub fn layout(&self) -> ChannelLayout {
+		let layout = ChannelLayout::from_mask(self.channel_mask);
+		match self.channel_mask {
+			0 => ChannelLayout::default_for(self.channels),
+			_ if layout.channels() != self.channels => ChannelLayout::default_for(self.channels),
+			_ => layout,
+		}
+	}
+
+	// what Microsoft asks for beyond stereo or 16 bits or with padded samples, pcm and float only
+	pub fn is_extensible(&self) -> bool {
+		let padded = self.valid_bits != 0 && self.valid_bits < self.bit_depth;
+		matches!(self.format_code, 1 | 3) && (self.channels > 2 || self.bit_depth > 16 || padded)
+	}
+
+	pub fn valid_bits_per_sample(&self) -> u16 {
+		match self.valid_bits {
+			0 => self.bit_depth,
+			bits => std::cmp::min(bits, self.bit_depth),
+		}
+	}
+
+	pub fn parameters(&self) -> CodecParameters {
+		let layout = self.layout();
+		let audio = AudioParameters::new(self.sample_rate, layout, self.bit_depth)
+			.with_valid_bits(self.valid_bits)
+			.with_block_align(self.block_align())
+			.with_bit_rate(self.byte_rate().saturating_mul(8));
+		match self.block_size {
+			0 => CodecParameters::Audio(audio),
+			_ => CodecParameters::Audio(audio.with_frame_size(self.block_samples())),
 		}
 	}
 
@@ -48,42 +148,76 @@ impl WavFormat {
 	}
 
 	pub fn byte_rate(&self) -> u32 {
-		self
-			.sample_rate
-			.saturating_mul(self.channels as u32)
-			.saturating_mul(self.bytes_per_sample() as u32)
+		let rate = self.sample_rate as u64 * self.block_align() as u64 / self.block_samples() as u64;
+		std::cmp::min(rate, u32::MAX as u64) as u32
 	}
 
 	pub fn block_align(&self) -> u16 {
-		self.channels as u16 * (self.bit_depth / 8)
+		match self.block_size {
+			0 => self.channels as u16 * (self.bit_depth / 8),
+			size => size,
+		}
+	}
+
+	// samples per channel in one block
+	pub fn block_samples(&self) -> u32 {
+		std::cmp::max(1, self.samples_per_block as u32)
+	}
+
+	// pcm and float are the only codes without a fact chunk
+	pub fn is_compressed(&self) -> bool {
+		!matches!(self.format_code, 1 | 3)
 	}
 
 	pub fn audio_format(&self) -> AudioFormat {
-		match self.bit_depth {
-			16 => AudioFormat::PCM16,
-			24 => AudioFormat::PCM24,
-			32 => AudioFormat::PCM32,
+		match (self.format_code, self.bit_depth) {
+			(_, 24) => AudioFormat::PCM24,
+			(3, 32) => AudioFormat::PCM32,
+			(_, 32) => AudioFormat::PCM32Int,
 			_ => AudioFormat::PCM16,
 		}
 	}
 
 	pub fn to_codec_string(&self) -> &'static str {
-		match self.bit_depth {
-			16 => codecs::audio::PCM_S16LE,
-			24 => codecs::audio::PCM_S24LE,
-			32 => codecs::audio::PCM_F32LE,
+		match (self.format_code, self.bit_depth) {
+			(6, _) => codecs::audio::PCM_ALAW,
+			(7, _) => codecs::audio::PCM_MULAW,
+			(2, _) => codecs::audio::ADPCM_MS,
+			(0x11, _) => codecs::audio::ADPCM_IMA_WAV,
+			(3, _) => codecs::audio::PCM_F32LE,
+			(_, 24) => codecs::audio::PCM_S24LE,
+			(_, 32) => codecs::audio::PCM_S32LE,
 			_ => codecs::audio::PCM_S16LE,
 		}
 	}
 
 	pub fn apply_codec(&mut self, codec: &str) -> Result<(), String> {
 		match codec {
-			codecs::audio::PCM_S16LE => self.bit_depth = 16,
-			codecs::audio::PCM_S24LE => self.bit_depth = 24,
+			codecs::audio::PCM_S16LE => (self.bit_depth, self.format_code) = (16, 1),
+			codecs::audio::PCM_S24LE => (self.bit_depth, self.format_code) = (24, 1),
+			codecs::audio::PCM_S32LE => (self.bit_depth, self.format_code) = (32, 1),
 			codecs::audio::PCM_F32LE => {
 				self.bit_depth = 32;
 				self.format_code = 3;
 			}
+			codecs::audio::PCM_ALAW => {
+				self.bit_depth = 8;
+				self.format_code = 6;
+			}
+			codecs::audio::PCM_MULAW => {
+				self.bit_depth = 8;
+				self.format_code = 7;
+			}
+			codecs::audio::ADPCM_IMA_WAV => {
+				self.bit_depth = 4;
+				self.format_code = 0x11;
+				self.with_adpcm_blocks(self.block_size, self.samples_per_block as u32);
+			}
+			codecs::audio::ADPCM_MS => {
+				self.bit_depth = 4;
+				self.format_code = 2;
+				self.with_adpcm_blocks(self.block_size, self.samples_per_block as u32);
+			}
 			_ => return Err(format!("wav codec '{}' is not supported", codec)),
 		}
 		Ok(())
@@ -1,8 +1,15 @@
 use crate::{
+	codecs::audio::adpcm,
 	container::wav::WavFormat,
 	io::{Error, Result},
 };
 
+pub const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+// KSDATAFORMAT_SUBTYPE_* guids are the plain format code followed by these bytes
+const SUB_FORMAT_SUFFIX: [u8; 14] =
+	[0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71];
+
 #[derive(Debug)]
 pub struct WavHeader {
 	pub channels: u8,
@@ -10,7 +17,29 @@ pub struct WavHeader {
 	pub byte_rate: u32,
 	pub block_align: u16,
 	pub bits_per_sample: u16,
+	// for an extensible fmt this is already the sub-format's code
 	pub format_code: u16,
+	// extensible only, 0 otherwise
+	pub valid_bits: u16,
+	pub channel_mask: u32,
+	// from the fmt extension of block codecs, 0 otherwise
+	pub samples_per_block: u16,
+	// what follows cbSize in a plain fmt, ms adpcm keeps its coefficients there
+	pub extension: Vec<u8>,
+}
+
+pub fn sub_format_code(guid: &[u8; 16]) -> Result<u16> {
+	if guid[2..] != SUB_FORMAT_SUFFIX {
+		return Err(Error::invalid_data("extensible sub-format is not supported"));
+	}
+	Ok(u16::from_le_bytes([guid[0], guid[1]]))
+}
+
+pub fn sub_format_guid(format_code: u16) -> [u8; 16] {
+	let mut guid = [0u8; 16];
+	guid[..2].copy_from_slice(&format_code.to_le_bytes());
+	guid[2..].copy_from_slice(&SUB_FORMAT_SUFFIX);
+	guid
 }
 
 impl WavHeader {
@@ -19,7 +48,11 @@ impl WavHeader {
 			channels: self.channels,
 			sample_rate: self.sample_rate,
 			bit_depth: self.bits_per_sample,
+			valid_bits: self.valid_bits,
 			format_code: self.format_code,
+			channel_mask: self.channel_mask,
+			block_size: if matches!(self.format_code, 2 | 0x11) { self.block_align } else { 0 },
+			samples_per_block: self.samples_per_block,
 		}
 	}
 
@@ -30,11 +63,17 @@ impl WavHeader {
 		if self.sample_rate == 0 {
 			return Err(Error::invalid_data("sample rate must be non-zero"));
 		}
+		if self.valid_bits > self.bits_per_sample {
+			return Err(Error::invalid_data("valid bits exceed bits per sample"));
+		}
 
 		match self.format_code {
-			1 | 3 => self.validate_pcm_bits(),
+			1 => self.validate_pcm_bits(),
+			3 => self.validate_float_bits(),
+			6 | 7 => self.validate_g711(),
+			2 => self.validate_ms_adpcm(),
 			0x11 => self.validate_ima_adpcm(),
-			code => Err(Error::invalid_data(&format!("audio format code {} is not supported", code))),
+			code => Err(Error::invalid_data(format!("audio format code {} is not supported", code))),
 		}
 	}
 
@@ -42,9 +81,28 @@ impl WavHeader {
 		if self.bits_per_sample == 0 {
 			return Err(Error::invalid_data("bits per sample must be non-zero"));
 		}
-		if self.bits_per_sample % 8 != 0 {
+		if !self.bits_per_sample.is_multiple_of(8) {
 			return Err(Error::invalid_data("bits per sample must be multiple of 8"));
 		}
+		if !matches!(self.bits_per_sample, 16 | 24 | 32) {
+			let message = format!("{} bit integer pcm is not supported", self.bits_per_sample);
+			return Err(Error::invalid_data(message));
+		}
+		Ok(())
+	}
+
+	pub fn validate_float_bits(&self) -> Result<()> {
+		if self.bits_per_sample != 32 {
+			let message = format!("{} bit float is not supported", self.bits_per_sample);
+			return Err(Error::invalid_data(message));
+		}
+		Ok(())
+	}
+
+	pub fn validate_g711(&self) -> Result<()> {
+		if self.bits_per_sample != 8 {
+			return Err(Error::invalid_data("G.711 must have 8 bits per sample"));
+		}
 		Ok(())
 	}
 
@@ -52,6 +110,28 @@ impl WavHeader {
 		if self.bits_per_sample != 4 {
 			return Err(Error::invalid_data("IMA ADPCM must have 4 bits per sample"));
 		}
+		let most = self.to_format().max_samples_per_block();
+		if most == 0 {
+			return Err(Error::invalid_data("IMA ADPCM block align does not fit the channels"));
+		}
+		if self.samples_per_block == 0 || self.samples_per_block > most {
+			return Err(Error::invalid_data("IMA ADPCM samples per block do not fit the block"));
+		}
+		Ok(())
+	}
+
+	pub fn validate_ms_adpcm(&self) -> Result<()> {
+		if self.bits_per_sample != 4 {
+			return Err(Error::invalid_data("MS ADPCM must have 4 bits per sample"));
+		}
+		let most = self.to_format().max_samples_per_block();
+		if most == 0 {
+			return Err(Error::invalid_data("MS ADPCM block align does not fit the channels"));
+		}
+		if self.samples_per_block == 0 || self.samples_per_block > most {
+			return Err(Error::invalid_data("MS ADPCM samples per block do not fit the block"));
+		}
+		adpcm::ms_coefficients(&self.extension)?;
 		Ok(())
 	}
 }
@@ -1,11 +1,67 @@
-pub mod converter;
 pub mod demuxer;
 pub mod formater;
 pub mod header;
-pub mod metadata;
 pub mod muxer;
+pub mod tags;
 pub mod utils;
 pub use demuxer::WavDemuxer;
 pub use formater::*;
-pub use metadata::WavMetadata;
 pub use muxer::WavMuxer;
+
+use crate::codecs::audio::{
+	ADPCM_IMA_WAV, ADPCM_MS, PCM_ALAW, PCM_F32LE, PCM_MULAW, PCM_S16LE, PCM_S24LE, PCM_S32LE,
+};
+use crate::container::{self, probe};
+use crate::core::registry::{ContainerEntry, DemuxerOptions, Registry};
+use crate::core::stream::Stream;
+use crate::core::{Demuxer, Muxer, SeekFlags};
+use crate::io::stdio::{StdioSink, StdioSource};
+use crate::io::{Error, ErrorKind, Result};
+
+pub fn register(registry: &mut Registry) {
+	let entry = ContainerEntry::new(container::WAV)
+		.with_codecs(&[
+			PCM_S16LE,
+			PCM_S24LE,
+			PCM_S32LE,
+			PCM_F32LE,
+			PCM_MULAW,
+			PCM_ALAW,
+			ADPCM_IMA_WAV,
+			ADPCM_MS,
+		])
+		.with_probe(probe::probe_wav)
+		.with_demuxer(open_demuxer)
+		.with_muxer(create_muxer);
+	registry.register_container(entry);
+}
+
+fn open_demuxer(source: StdioSource, options: &DemuxerOptions) -> Result<Box<dyn Demuxer>> {
+	let (start, end) = options.sample_range.unwrap_or((0, None));
+	// files can seek, the data before the range is never read
+	let mut demuxer = WavDemuxer::new(source)?;
+	match demuxer.read_trailing_chunks() {
+		Err(error) if !matches!(error.kind(), ErrorKind::NotSeekable) => return Err(error),
+		_ => {}
+	}
+	let mut demuxer = demuxer.with_sample_range(0, end)?;
+	match demuxer.seek(start as i64, SeekFlags::Exact) {
+		Ok(_) => Ok(Box::new(demuxer)),
+		// pipes read through the data before the range instead
+		Err(error) if matches!(error.kind(), ErrorKind::NotSeekable) => {
+			Ok(Box::new(demuxer.with_sample_range(start, end)?))
+		}
+		Err(error) => Err(error),
+	}
+}
+
+// holds exactly one audio stream
+fn create_muxer(sink: StdioSink, streams: &[Stream]) -> Result<Box<dyn Muxer>> {
+	let [stream] = streams else {
+		return Err(Error::invalid_data("wav holds exactly one stream"));
+	};
+	if !stream.is_audio() {
+		return Err(Error::invalid_data("wav holds audio only"));
+	}
+	Ok(Box::new(WavMuxer::new(sink, WavFormat::from_stream(stream)?)?))
+}
@@ -1,5 +1,9 @@
-use crate::container::wav::{WavFormat, WavMetadata};
+use crate::codecs::audio::adpcm::table::MS_COEFFICIENTS;
+use crate::container::wav::WavFormat;
+use crate::container::wav::header::{WAVE_FORMAT_EXTENSIBLE, sub_format_guid};
+use crate::container::wav::tags;
 use crate::core::Muxer;
+use crate::core::metadata::{Broadcast, Metadata, Sampler};
 use crate::core::packet::Packet;
 use crate::core::stream::{self, Stream, StreamKind};
 use crate::core::time::Time;
@@ -7,140 +11,363 @@ use crate::io::{MediaSeek, MediaWrite, Result, SeekFrom, WritePrimitives};
 
 pub struct WavMuxer<W: MediaWrite + MediaSeek> {
 	writer: W,
-	#[allow(dead_code)]
 	format: WavFormat,
 	streams: stream::Streams,
-	metadata: Option<WavMetadata>,
-	data_size: u32,
+	metadata: Option<Metadata>,
+	data_size: u64,
+	// samples per channel, for the fact chunk of compressed data
+	samples: u64,
+	fact_pos: Option<u64>,
 	data_size_pos: u64,
 	file_size_pos: u64,
+	junk_pos: u64,
+	// the writer cannot seek, sizes stay at their placeholder and tags go before the data
+	streaming: bool,
+	data_started: bool,
+	// sizes from here on go into a ds64 chunk
+	size_limit: u64,
 }
 
 impl<W: MediaWrite + MediaSeek> WavMuxer<W> {
+	const UNKNOWN_SIZE: u32 = u32::MAX;
+	// what a ds64 chunk without a size table holds: riff, data and sample count, table length
+	const DS64_SIZE: u32 = 28;
+
 	pub fn new(mut writer: W, format: WavFormat) -> Result<Self> {
-		let (file_size_pos, data_size_pos) = Self::write_header(&mut writer, &format)?;
+		let streaming = writer.stream_position().is_err();
+		let size = if streaming { Self::UNKNOWN_SIZE } else { 0 };
+		// room for a ds64 chunk, should the data outgrow 32 bit sizes
+		let mut header_size = Self::write_header(&mut writer, &format, size, !streaming)?;
+		let mut fact_pos = None;
+		if format.is_compressed() {
+			writer.write_all(b"fact")?;
+			writer.write_u32_le(4)?;
+			writer.write_u32_le(size)?;
+			fact_pos = Some(header_size + 8);
+			header_size += 12;
+		}
+		if !streaming {
+			Self::write_data_header(&mut writer, 0)?;
+		}
 		writer.flush()?;
 
 		let codec_name = format.to_codec_string().to_string();
 		let time = Time::new(1, format.sample_rate);
 		let mut streams = stream::Streams::new_empty();
-		let stream = Stream::new(0, 0, StreamKind::Audio, codec_name, time);
+		let stream =
+			Stream::new(0, 0, StreamKind::Audio, codec_name, time).with_parameters(format.parameters());
 
 		streams.add(stream);
 
-		Ok(Self { writer, format, streams, metadata: None, data_size: 0, data_size_pos, file_size_pos })
+		Ok(Self {
+			writer,
+			format,
+			streams,
+			metadata: None,
+			data_size: 0,
+			samples: 0,
+			fact_pos,
+			data_size_pos: header_size + 4,
+			file_size_pos: 4,
+			junk_pos: 12,
+			streaming,
+			data_started: !streaming,
+			size_limit: Self::UNKNOWN_SIZE as u64,
+		})
 	}
 
-	pub fn with_metadata(&mut self, metadata: Option<WavMetadata>) {
+	// lets a small file take the RF64 path
+	#[cfg(test)]
+	fn with_size_limit(mut self, size_limit: u64) -> Self {
+		self.size_limit = size_limit;
+		self
+	}
+
+	pub fn with_metadata(&mut self, metadata: Option<Metadata>) {
 		self.metadata = metadata;
 	}
 
-	fn write_header(writer: &mut W, format: &WavFormat) -> Result<(u64, u64)> {
+	// everything up to the data chunk, returns its length
+	fn write_header(writer: &mut W, format: &WavFormat, size: u32, junk: bool) -> Result<u64> {
 		writer.write_all(b"RIFF")?;
-		let file_size_pos = writer.stream_position()?;
-		writer.write_u32_le(0)?;
+		writer.write_u32_le(size)?;
 		writer.write_all(b"WAVE")?;
+		let mut header_size = 12;
+		if junk {
+			writer.write_all(b"JUNK")?;
+			writer.write_u32_le(Self::DS64_SIZE)?;
+			writer.write_all(&vec![0; Self::DS64_SIZE as usize])?;
+			header_size += 8 + Self::DS64_SIZE as u64;
+		}
 		writer.write_all(b"fmt ")?;
 
+		let extensible = format.is_extensible();
 		let fmt_size = match format.format_code {
-			3 => 18,
+			_ if extensible => 40,
+			3 | 6 | 7 => 18,
+			2 => 22 + 4 * MS_COEFFICIENTS.len() as u32,
 			0x11 => 20,
 			_ => 16,
 		};
 		writer.write_u32_le(fmt_size)?;
-		writer.write_u16_le(format.format_code)?;
+		writer.write_u16_le(if extensible { WAVE_FORMAT_EXTENSIBLE } else { format.format_code })?;
 		writer.write_u16_le(format.channels as u16)?;
 		writer.write_u32_le(format.sample_rate)?;
 		writer.write_u32_le(format.byte_rate())?;
 		writer.write_u16_le(format.block_align())?;
 		writer.write_u16_le(format.bit_depth)?;
 
-		if format.format_code == 3 {
+		if extensible {
+			writer.write_u16_le(22)?;
+			writer.write_u16_le(format.valid_bits_per_sample())?;
+			writer.write_u32_le(format.layout().mask())?;
+			writer.write_all(&sub_format_guid(format.format_code))?;
+		} else if matches!(format.format_code, 3 | 6 | 7) {
 			writer.write_u16_le(0)?;
 		} else if format.format_code == 0x11 {
-			writer.write_u16_le(4)?;
-			let spb = ((512 - 4 * format.channels as usize) * 2 + 1) as u16;
-			writer.write_u16_le(spb)?;
+			writer.write_u16_le(2)?;
+			writer.write_u16_le(format.samples_per_block)?;
+		} else if format.format_code == 2 {
+			// the encoder only picks from the standard coefficients
+			writer.write_u16_le(fmt_size as u16 - 18)?;
+			writer.write_u16_le(format.samples_per_block)?;
+			writer.write_u16_le(MS_COEFFICIENTS.len() as u16)?;
+			for [first, second] in MS_COEFFICIENTS {
+				writer.write_i16_le(first)?;
+				writer.write_i16_le(second)?;
+			}
 		}
 
+		Ok(header_size + 8 + fmt_size as u64)
+	}
+
+	fn write_data_header(writer: &mut W, size: u32) -> Result<()> {
 		writer.write_all(b"data")?;
-		let data_size_pos = writer.stream_position()?;
-		writer.write_u32_le(0)?;
-		Ok((file_size_pos, data_size_pos))
+		writer.write_u32_le(size)
+	}
+
+	// a streamed file has its tags ahead of the data, nothing can follow data of unknown size
+	fn start_streamed_data(&mut self) -> Result<()> {
+		if let Some(meta) = &self.metadata {
+			Self::write_metadata_chunks(&mut self.writer, meta)?;
+		}
+		Self::write_data_header(&mut self.writer, Self::UNKNOWN_SIZE)?;
+		self.data_started = true;
+		Ok(())
 	}
 
 	pub fn write_packet(&mut self, packet: Packet) -> Result<()> {
+		if !self.data_started {
+			self.start_streamed_data()?;
+		}
 		self.writer.write_all(&packet.data)?;
-		self.data_size += packet.data.len() as u32;
+		self.data_size += packet.data.len() as u64;
+		self.samples += match packet.duration {
+			duration if duration > 0 => duration as u64,
+			_ => {
+				let blocks = (packet.data.len() as u64).div_ceil(self.format.block_align() as u64);
+				blocks * self.format.block_samples() as u64
+			}
+		};
 		Ok(())
 	}
 
 	pub fn finalize(&mut self) -> Result<()> {
-		self.writer.seek(SeekFrom::Start(self.data_size_pos))?;
-		self.writer.write_u32_le(self.data_size)?;
-
-		let mut file_size = self.data_size + 36;
+		if self.streaming {
+			if !self.data_started {
+				self.start_streamed_data()?;
+			}
+			return self.writer.flush();
+		}
 
 		if let Some(meta) = &self.metadata {
-			if !meta.is_empty() {
-				file_size += Self::calc_list_size(meta) as u32;
-				self.writer.seek(SeekFrom::End(0))?;
-				Self::write_list_chunk(&mut self.writer, meta)?;
+			self.writer.seek(SeekFrom::End(0))?;
+			if self.data_size % 2 == 1 {
+				self.writer.write_u8(0)?;
 			}
+			Self::write_metadata_chunks(&mut self.writer, meta)?;
+		}
+		let file_size = self.writer.seek(SeekFrom::End(0))? - 8;
+		if let Some(fact_pos) = self.fact_pos {
+			self.writer.seek(SeekFrom::Start(fact_pos))?;
+			self.writer.write_u32_le(std::cmp::min(self.samples, Self::UNKNOWN_SIZE as u64) as u32)?;
 		}
 
-		self.writer.seek(SeekFrom::Start(self.file_size_pos))?;
-		self.writer.write_u32_le(file_size)?;
+		// past 4 GiB the file becomes RF64 and its real sizes move into the ds64 chunk
+		if file_size >= self.size_limit || self.data_size >= self.size_limit {
+			self.write_ds64(file_size)?;
+		} else {
+			self.writer.seek(SeekFrom::Start(self.data_size_pos))?;
+			self.writer.write_u32_le(self.data_size as u32)?;
+			self.writer.seek(SeekFrom::Start(self.file_size_pos))?;
+			self.writer.write_u32_le(file_size as u32)?;
+		}
 		self.writer.flush()?;
 		Ok(())
 	}
 
-	fn calc_list_size(metadata: &WavMetadata) -> u64 {
-		metadata.all_fields().values().fold(8, |acc, v| {
-			let mut size = acc + 8 + v.len() as u64 + 1;
-			if (v.len() + 1) % 2 == 1 {
-				size += 1;
-			}
-			size
-		})
+	fn write_ds64(&mut self, file_size: u64) -> Result<()> {
+		self.writer.seek(SeekFrom::Start(0))?;
+		self.writer.write_all(b"RF64")?;
+		self.writer.write_u32_le(Self::UNKNOWN_SIZE)?;
+
+		self.writer.seek(SeekFrom::Start(self.junk_pos))?;
+		self.writer.write_all(b"ds64")?;
+		self.writer.write_u32_le(Self::DS64_SIZE)?;
+		self.writer.write_u64_le(file_size)?;
+		self.writer.write_u64_le(self.data_size)?;
+		self.writer.write_u64_le(self.samples)?;
+		self.writer.write_u32_le(0)?;
+
+		self.writer.seek(SeekFrom::Start(self.data_size_pos))?;
+		self.writer.write_u32_le(Self::UNKNOWN_SIZE)
 	}
 
-	fn write_list_chunk(writer: &mut W, metadata: &WavMetadata) -> Result<()> {
-		if metadata.is_empty() {
-			return Ok(());
+	fn write_metadata_chunks(writer: &mut W, metadata: &Metadata) -> Result<()> {
+		Self::write_list_chunk(writer, metadata)?;
+		if let Some(id3) = tags::write_id3(metadata) {
+			Self::write_padded_chunk(writer, b"id3 ", &id3)?;
+		}
+		if let Some(broadcast) = &metadata.broadcast {
+			Self::write_bext_chunk(writer, broadcast)?;
+		}
+		if let Some(ixml) = &metadata.ixml {
+			Self::write_padded_chunk(writer, b"iXML", ixml.as_bytes())?;
 		}
+		if !metadata.markers.is_empty() {
+			Self::write_cue_chunk(writer, metadata)?;
+		}
+		if let Some(sampler) = &metadata.sampler {
+			Self::write_smpl_chunk(writer, sampler)?;
+		}
+		Ok(())
+	}
 
-		let list_size = Self::calc_list_size(metadata) - 8;
-		writer.write_all(b"LIST")?;
-		writer.write_u32_le(list_size as u32)?;
-		writer.write_all(b"INFO")?;
-
-		for (field, value) in metadata.all_fields() {
-			let id: &[u8; 4] = match field.as_str() {
-				"artist" => b"IART",
-				"title" => b"INAM",
-				"comment" => b"ICOM",
-				"copyright" => b"ICOP",
-				"software" => b"ISFT",
-				"genre" => b"IGNR",
-				"track" => b"ITRK",
-				_ => continue,
+	fn write_bext_chunk(writer: &mut W, broadcast: &Broadcast) -> Result<()> {
+		let mut data = Vec::with_capacity(602 + broadcast.coding_history.len());
+		let text = |data: &mut Vec<u8>, value: &str, size: usize| {
+			let bytes = &value.as_bytes()[..std::cmp::min(value.len(), size)];
+			data.extend(bytes);
+			data.resize(data.len() + size - bytes.len(), 0);
+		};
+		text(&mut data, &broadcast.description, 256);
+		text(&mut data, &broadcast.originator, 32);
+		text(&mut data, &broadcast.originator_reference, 32);
+		text(&mut data, &broadcast.origination_date, 10);
+		text(&mut data, &broadcast.origination_time, 8);
+		data.extend(broadcast.time_reference.to_le_bytes());
+		data.extend(broadcast.version.to_le_bytes());
+		let umid = &broadcast.umid[..std::cmp::min(broadcast.umid.len(), 64)];
+		data.extend(umid);
+		data.resize(data.len() + 64 - umid.len(), 0);
+		for loudness in [
+			broadcast.loudness_value,
+			broadcast.loudness_range,
+			broadcast.max_true_peak_level,
+			broadcast.max_momentary_loudness,
+			broadcast.max_short_term_loudness,
+		] {
+			data.extend(loudness.to_le_bytes());
+		}
+		data.resize(data.len() + 180, 0);
+		data.extend(broadcast.coding_history.as_bytes());
+		Self::write_padded_chunk(writer, b"bext", &data)
+	}
+
+	// cue points with their labels in a LIST adtl right after
+	fn write_cue_chunk(writer: &mut W, metadata: &Metadata) -> Result<()> {
+		let mut data = (metadata.markers.len() as u32).to_le_bytes().to_vec();
+		for marker in &metadata.markers {
+			let position = marker.position as u32;
+			data.extend(marker.id.to_le_bytes());
+			data.extend(position.to_le_bytes());
+			data.extend(b"data");
+			data.extend([0; 8]);
+			data.extend(position.to_le_bytes());
+		}
+		Self::write_padded_chunk(writer, b"cue ", &data)?;
+
+		let mut adtl = b"adtl".to_vec();
+		for marker in &metadata.markers {
+			let Some(label) = &marker.label else {
+				continue;
 			};
-			Self::write_info_chunk(writer, id, value)?;
+			let size = 4 + label.len() as u32 + 1;
+			adtl.extend(b"labl");
+			adtl.extend(size.to_le_bytes());
+			adtl.extend(marker.id.to_le_bytes());
+			adtl.extend(label.as_bytes());
+			adtl.push(0);
+			if size % 2 == 1 {
+				adtl.push(0);
+			}
+		}
+		if adtl.len() > 4 {
+			Self::write_padded_chunk(writer, b"LIST", &adtl)?;
 		}
 		Ok(())
 	}
 
-	fn write_info_chunk(writer: &mut W, id: &[u8; 4], value: &str) -> Result<()> {
-		let data = format!("{}\0", value);
+	fn write_smpl_chunk(writer: &mut W, sampler: &Sampler) -> Result<()> {
+		let mut data = Vec::with_capacity(36 + sampler.loops.len() * 24);
+		for value in [
+			sampler.manufacturer,
+			sampler.product,
+			sampler.sample_period,
+			sampler.midi_unity_note,
+			sampler.midi_pitch_fraction,
+			sampler.smpte_format,
+			sampler.smpte_offset,
+			sampler.loops.len() as u32,
+			0,
+		] {
+			data.extend(value.to_le_bytes());
+		}
+		for lp in &sampler.loops {
+			for value in [lp.id, lp.kind, lp.start as u32, lp.end as u32, lp.fraction, lp.play_count] {
+				data.extend(value.to_le_bytes());
+			}
+		}
+		Self::write_padded_chunk(writer, b"smpl", &data)
+	}
+
+	fn write_padded_chunk(writer: &mut W, id: &[u8; 4], data: &[u8]) -> Result<()> {
 		writer.write_all(id)?;
 		writer.write_u32_le(data.len() as u32)?;
-		writer.write_all(data.as_bytes())?;
+		writer.write_all(data)?;
 		if data.len() % 2 == 1 {
 			writer.write_u8(0)?;
 		}
 		Ok(())
 	}
+
+	// every tag with an INFO id, sorted so the output does not depend on map order
+	fn write_list_chunk(writer: &mut W, metadata: &Metadata) -> Result<()> {
+		let mut fields: Vec<_> = metadata
+			.all_fields()
+			.iter()
+			.filter_map(|(key, value)| tags::info_id(key).map(|id| (id, value)))
+			.collect();
+		if fields.is_empty() {
+			return Ok(());
+		}
+		// a raw id like "ieng" and its name both end up as IENG
+		fields.sort();
+		fields.dedup_by_key(|(id, _)| *id);
+
+		let mut list = b"INFO".to_vec();
+		for (id, value) in fields {
+			let size = value.len() as u32 + 1;
+			list.extend(id);
+			list.extend(size.to_le_bytes());
+			list.extend(value.as_bytes());
+			list.push(0);
+			if size % 2 == 1 {
+				list.push(0);
+			}
+		}
+		Self::write_padded_chunk(writer, b"LIST", &list)
+	}
 }
 
 impl<W: MediaWrite + MediaSeek> Muxer for WavMuxer<W> {
@@ -153,4 +380,102 @@ impl<W: MediaWrite + MediaSeek> Muxer for WavMuxer<W> {
 	fn finalize(&mut self) -> Result<()> {
 		self.finalize()
 	}
+	fn set_metadata(&mut self, metadata: Metadata) {
+		self.with_metadata(Some(metadata));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::container::wav::WavDemuxer;
+	use crate::core::Demuxer;
+	use crate::core::metadata::{Marker, SampleLoop};
+	use crate::core::time::Time;
+	use crate::io::Cursor;
+
+	#[test]
+	fn test_chunks_after_the_data_roundtrip() {
+		let mut metadata = Metadata::new();
+		metadata.set_title("take 3".to_string());
+		metadata.broadcast = Some(Broadcast {
+			description: "scene 12".to_string(),
+			originator: "recorder".to_string(),
+			origination_date: "2026-10-18".to_string(),
+			time_reference: 1_728_000_000,
+			version: 2,
+			umid: vec![7; 64],
+			loudness_value: -2300,
+			coding_history: "A=PCM,F=48000,W=16,M=stereo\r\n".to_string(),
+			..Default::default()
+		});
+		metadata.ixml = Some("<BWFXML><SCENE>12</SCENE></BWFXML>".to_string());
+		metadata.markers = vec![
+			Marker { id: 1, position: 0, label: Some("slate".to_string()) },
+			Marker { id: 2, position: 3, label: None },
+		];
+		metadata.sampler = Some(Sampler {
+			midi_unity_note: 60,
+			loops: vec![SampleLoop { id: 2, kind: 0, start: 3, end: 9, fraction: 0, play_count: 0 }],
+			..Default::default()
+		});
+
+		let mut muxer = WavMuxer::new(Cursor::new(Vec::new()), WavFormat::default()).unwrap();
+		muxer.with_metadata(Some(metadata.clone()));
+		muxer.write_packet(Packet::new(vec![1; 40], 0, Time::new(1, 44100))).unwrap();
+		muxer.finalize().unwrap();
+
+		let mut demuxer = WavDemuxer::new(Cursor::new(muxer.writer.into_inner())).unwrap();
+		demuxer.read_trailing_chunks().unwrap();
+		let read = demuxer.metadata();
+		assert_eq!(read.title(), Some("take 3"));
+		assert_eq!(read.broadcast, metadata.broadcast);
+		assert_eq!(read.ixml, metadata.ixml);
+		assert_eq!(read.markers, metadata.markers);
+		assert_eq!(read.sampler, metadata.sampler);
+		assert_eq!(demuxer.read_packet().unwrap().map(|packet| packet.data), Some(vec![1; 40]));
+	}
+
+	#[test]
+	fn test_valid_bits_roundtrip() {
+		// 20 bit samples padded to 24
+		let format = WavFormat { bit_depth: 24, valid_bits: 20, ..WavFormat::default() };
+		let mut muxer = WavMuxer::new(Cursor::new(Vec::new()), format).unwrap();
+		muxer.write_packet(Packet::new(vec![0x10; 36], 0, Time::new(1, 44100))).unwrap();
+		muxer.finalize().unwrap();
+
+		let demuxer = WavDemuxer::new(Cursor::new(muxer.writer.into_inner())).unwrap();
+		let stream = demuxer.streams().get(0).unwrap();
+		assert_eq!(stream.audio_parameters().map(|audio| audio.valid_bits), Some(20));
+		let format = WavFormat::from_stream(stream).unwrap();
+		assert_eq!((format.bit_depth, format.valid_bits_per_sample()), (24, 20));
+	}
+
+	#[test]
+	fn test_ds64_upgrade_reads_back() {
+		// 80 bytes of header and 40 of data, past the limit the file is finalized as RF64
+		let write = |size_limit| {
+			let muxer = WavMuxer::new(Cursor::new(Vec::new()), WavFormat::default()).unwrap();
+			let mut muxer = muxer.with_size_limit(size_limit);
+			Muxer::write(&mut muxer, Packet::new(vec![1; 40], 0, Time::new(1, 44100))).unwrap();
+			Muxer::finalize(&mut muxer).unwrap();
+			muxer.writer.into_inner()
+		};
+		assert_eq!(&write(200)[0..4], b"RIFF");
+		let bytes = write(100);
+
+		let word = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+		let long = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+		assert_eq!(&bytes[0..4], b"RF64");
+		assert_eq!(word(4), u32::MAX);
+		assert_eq!((&bytes[12..16], word(16)), (&b"ds64"[..], 28));
+		assert_eq!((long(20), long(28), long(36), word(44)), (bytes.len() as u64 - 8, 40, 10, 0));
+		let data = bytes.windows(4).position(|id| id == b"data").unwrap();
+		assert_eq!(word(data + 4), u32::MAX);
+
+		let mut demuxer = WavDemuxer::new(Cursor::new(bytes)).unwrap();
+		assert_eq!(demuxer.streams().get(0).and_then(|stream| stream.duration), Some(10));
+		assert_eq!(demuxer.read_packet().unwrap().map(|packet| packet.data), Some(vec![1; 40]));
+		assert!(demuxer.read_packet().unwrap().is_none());
+	}
 }
@@ -0,0 +1,205 @@
+use crate::core::metadata::Metadata;
+
+// LIST INFO ids with a common name, any other id is kept under its own name in lowercase
+const INFO_KEYS: [(&str, &str); 17] = [
+	("IART", "artist"),
+	("INAM", "title"),
+	("IPRD", "album"),
+	("ICMT", "comment"),
+	("ICOP", "copyright"),
+	("ICRD", "date"),
+	("IGNR", "genre"),
+	("ITRK", "track"),
+	("ISFT", "software"),
+	("IENG", "engineer"),
+	("ITCH", "technician"),
+	("ICMS", "commissioned"),
+	("IKEY", "keywords"),
+	("ISBJ", "subject"),
+	("ISRC", "source"),
+	("ILNG", "language"),
+	("IMED", "medium"),
+];
+
+// ID3v2 frames written for the same names, `date` is TYER in version 3
+const ID3_KEYS: [(&str, &str); 10] = [
+	("TPE1", "artist"),
+	("TIT2", "title"),
+	("TALB", "album"),
+	("COMM", "comment"),
+	("TCOP", "copyright"),
+	("TDRC", "date"),
+	("TCON", "genre"),
+	("TRCK", "track"),
+	("TSSE", "software"),
+	("TCOM", "composer"),
+];
+
+pub fn info_key(id: &str) -> String {
+	match INFO_KEYS.iter().find(|(info, _)| *info == id) {
+		Some((_, key)) => key.to_string(),
+		None => id.to_lowercase(),
+	}
+}
+
+// names without an INFO id of their own have none, they only go to the id3 chunk
+pub fn info_id(key: &str) -> Option<[u8; 4]> {
+	let id = match INFO_KEYS.iter().find(|(_, name)| *name == key) {
+		Some((id, _)) => id.to_string(),
+		None if key.len() == 4 && key.starts_with('i') => key.to_uppercase(),
+		None => return None,
+	};
+	match id.as_bytes() {
+		&[a, b, c, d] if id.bytes().all(|b| b.is_ascii_alphanumeric()) => Some([a, b, c, d]),
+		_ => None,
+	}
+}
+
+// text frames of an ID3v2.3 or 2.4 tag, anything else in it is skipped
+pub fn read_id3(data: &[u8], metadata: &mut Metadata) {
+	if data.len() < 10 || &data[..3] != b"ID3" || !matches!(data[3], 3 | 4) {
+		return;
+	}
+	let version = data[3];
+	let flags = data[5];
+	let size = std::cmp::min(synchsafe(&data[6..10]) as usize, data.len() - 10);
+	let mut tag = data[10..10 + size].to_vec();
+	if flags & 0x80 != 0 && version == 3 {
+		tag = unsynchronise(&tag);
+	}
+
+	let mut position = 0;
+	if flags & 0x40 != 0 && tag.len() >= 4 {
+		position = match version {
+			3 => 4 + u32::from_be_bytes([tag[0], tag[1], tag[2], tag[3]]) as usize,
+			_ => synchsafe(&tag[..4]) as usize,
+		};
+	}
+
+	while position + 10 <= tag.len() && tag[position] != 0 {
+		let id = String::from_utf8_lossy(&tag[position..position + 4]).to_string();
+		let bytes = &tag[position + 4..position + 8];
+		let size = match version {
+			3 => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize,
+			_ => synchsafe(bytes) as usize,
+		};
+		let start = position + 10;
+		let end = std::cmp::min(start + size, tag.len());
+		position = end;
+
+		let key = match id.as_str() {
+			"TYER" => "date",
+			id => match ID3_KEYS.iter().find(|(frame, _)| *frame == id) {
+				Some((_, key)) => key,
+				None => continue,
+			},
+		};
+		let frame = &tag[start..end];
+		let value = match (id.as_str(), frame.split_first()) {
+			// language and a short description go before the text
+			("COMM", Some((&encoding, rest))) if rest.len() >= 3 => {
+				let text = decode_text(encoding, &rest[3..]);
+				text.split_once('\0').map(|(_, text)| text.to_string()).unwrap_or(text)
+			}
+			(_, Some((&encoding, rest))) => decode_text(encoding, rest),
+			(_, None) => continue,
+		};
+		let value = value.trim_end_matches('\0').replace('\0', "/");
+		if !value.is_empty() {
+			metadata.set(key, value);
+		}
+	}
+}
+
+// an ID3v2.4 tag in UTF-8, None when no name has a frame
+pub fn write_id3(metadata: &Metadata) -> Option<Vec<u8>> {
+	let mut frames = Vec::new();
+	for (id, key) in ID3_KEYS {
+		let Some(value) = metadata.get(key) else {
+			continue;
+		};
+		let mut frame = vec![3];
+		if id == "COMM" {
+			frame.extend(b"eng\0");
+		}
+		frame.extend(value.as_bytes());
+
+		frames.extend(id.as_bytes());
+		frames.extend(to_synchsafe(frame.len() as u32));
+		frames.extend([0, 0]);
+		frames.extend(frame);
+	}
+	if frames.is_empty() {
+		return None;
+	}
+
+	let mut tag = b"ID3\x04\x00\x00".to_vec();
+	tag.extend(to_synchsafe(frames.len() as u32));
+	tag.extend(frames);
+	Some(tag)
+}
+
+fn decode_text(encoding: u8, bytes: &[u8]) -> String {
+	match encoding {
+		0 => bytes.iter().map(|&b| b as char).collect(),
+		1 | 2 => {
+			let big_endian = encoding == 2 || bytes.starts_with(&[0xFE, 0xFF]);
+			let skip = if encoding == 1 && bytes.len() >= 2 { 2 } else { 0 };
+			let units = bytes[skip..].chunks_exact(2).map(|pair| match big_endian {
+				true => u16::from_be_bytes([pair[0], pair[1]]),
+				false => u16::from_le_bytes([pair[0], pair[1]]),
+			});
+			char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+		}
+		_ => String::from_utf8_lossy(bytes).to_string(),
+	}
+}
+
+fn synchsafe(bytes: &[u8]) -> u32 {
+	bytes.iter().fold(0, |size, &b| (size << 7) | (b & 0x7F) as u32)
+}
+
+fn to_synchsafe(size: u32) -> [u8; 4] {
+	[
+		(size >> 21) as u8 & 0x7F,
+		(size >> 14) as u8 & 0x7F,
+		(size >> 7) as u8 & 0x7F,
+		size as u8 & 0x7F,
+	]
+}
+
+// drops the zero byte stuffed after every 0xFF
+fn unsynchronise(data: &[u8]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(data.len());
+	for (index, &b) in data.iter().enumerate() {
+		if b == 0 && index > 0 && data[index - 1] == 0xFF {
+			continue;
+		}
+		out.push(b);
+	}
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_id3_roundtrip_and_info_names() {
+		let mut metadata = Metadata::new();
+		metadata.set("title", "Ünder".to_string());
+		metadata.set("comment", "first take".to_string());
+		metadata.set("engineer", "sam".to_string());
+
+		let mut read = Metadata::new();
+		read_id3(&write_id3(&metadata).unwrap(), &mut read);
+		assert_eq!(read.title(), Some("Ünder"));
+		assert_eq!(read.get("comment"), Some("first take"));
+		assert_eq!(read.get("engineer"), None);
+
+		assert_eq!(info_key("IENG"), "engineer");
+		assert_eq!(info_key("IXYZ"), "ixyz");
+		assert_eq!(info_id("ixyz"), Some(*b"IXYZ"));
+		assert_eq!(info_id("composer"), None);
+	}
+}
@@ -35,27 +35,3 @@
 // 		)
 // 	}
 // }
-
-pub fn normalize_pcm16(sample: i16) -> f32 {
-	sample as f32 / 32768.0
-}
-
-pub fn denormalize_pcm16(normalized: f32) -> i16 {
-	(normalized * 32768.0).clamp(-32768.0, 32767.0) as i16
-}
-
-pub fn normalize_pcm24(sample: i32) -> f32 {
-	(sample >> 8) as f32 / 8388608.0
-}
-
-pub fn denormalize_pcm24(normalized: f32) -> i32 {
-	((normalized * 8388608.0).clamp(-8388608.0, 8388607.0) as i32) << 8
-}
-
-pub fn normalize_pcm32(sample: f32) -> f32 {
-	sample.clamp(-1.0, 1.0)
-}
-
-pub fn denormalize_pcm32(normalized: f32) -> f32 {
-	normalized.clamp(-1.0, 1.0)
-}
@@ -1,8 +1,14 @@
+use super::layout::ChannelLayout;
+use super::sample;
+use crate::io::Result;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AudioFormat {
 	PCM16,
 	PCM24,
+	// 32 bit float, integers of that size are PCM32Int
 	PCM32,
+	PCM32Int,
 	FLAC,
 	AAC,
 	Opus,
@@ -14,7 +20,7 @@ impl AudioFormat {
 		match self {
 			AudioFormat::PCM16 => 2,
 			AudioFormat::PCM24 => 3,
-			AudioFormat::PCM32 => 4,
+			AudioFormat::PCM32 | AudioFormat::PCM32Int => 4,
 			AudioFormat::FLAC | AudioFormat::AAC | AudioFormat::Opus | AudioFormat::ADPCM => 1,
 		}
 	}
@@ -25,6 +31,7 @@ pub struct FrameAudio {
 	pub data: Vec<u8>,
 	pub sample_rate: u32,
 	pub channels: u8,
+	pub layout: ChannelLayout,
 	pub nb_samples: usize,
 	pub format: AudioFormat,
 }
@@ -32,7 +39,13 @@ pub struct FrameAudio {
 impl FrameAudio {
 	pub fn new(data: Vec<u8>, sample_rate: u32, channels: u8, format: AudioFormat) -> Self {
 		let nb_samples = data.len() / (channels as usize * format.bytes_per_sample());
-		Self { data, sample_rate, channels, nb_samples, format }
+		let layout = ChannelLayout::default_for(channels);
+		Self { data, sample_rate, channels, layout, nb_samples, format }
+	}
+
+	pub fn with_layout(mut self, layout: ChannelLayout) -> Self {
+		self.layout = layout;
+		self
 	}
 
 	pub fn with_nb_samples(mut self, nb_samples: usize) -> Self {
@@ -43,4 +56,14 @@ impl FrameAudio {
 	pub fn bytes_per_sample(&self) -> usize {
 		self.format.bytes_per_sample()
 	}
+
+	pub fn to_f32(&self) -> Result<Vec<f32>> {
+		sample::to_f32(&self.data, self.format)
+	}
+
+	pub fn write_f32(&mut self, samples: &[f32]) -> Result<()> {
+		self.data = sample::from_f32(samples, self.format)?;
+		self.nb_samples = samples.len() / std::cmp::max(1, self.channels as usize);
+		Ok(())
+	}
 }
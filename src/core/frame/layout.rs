@@ -0,0 +1,166 @@
+use crate::io::{Error, Result};
+
+// speaker positions, bit values follow the WAVE_FORMAT_EXTENSIBLE channel mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Speaker {
+	FrontLeft = 0x1,
+	FrontRight = 0x2,
+	FrontCenter = 0x4,
+	LowFrequency = 0x8,
+	BackLeft = 0x10,
+	BackRight = 0x20,
+	FrontLeftOfCenter = 0x40,
+	FrontRightOfCenter = 0x80,
+	BackCenter = 0x100,
+	SideLeft = 0x200,
+	SideRight = 0x400,
+	TopCenter = 0x800,
+	TopFrontLeft = 0x1000,
+	TopFrontCenter = 0x2000,
+	TopFrontRight = 0x4000,
+	TopBackLeft = 0x8000,
+	TopBackCenter = 0x10000,
+	TopBackRight = 0x20000,
+}
+
+impl Speaker {
+	pub const ALL: [Speaker; 18] = [
+		Speaker::FrontLeft,
+		Speaker::FrontRight,
+		Speaker::FrontCenter,
+		Speaker::LowFrequency,
+		Speaker::BackLeft,
+		Speaker::BackRight,
+		Speaker::FrontLeftOfCenter,
+		Speaker::FrontRightOfCenter,
+		Speaker::BackCenter,
+		Speaker::SideLeft,
+		Speaker::SideRight,
+		Speaker::TopCenter,
+		Speaker::TopFrontLeft,
+		Speaker::TopFrontCenter,
+		Speaker::TopFrontRight,
+		Speaker::TopBackLeft,
+		Speaker::TopBackCenter,
+		Speaker::TopBackRight,
+	];
+
+	pub fn mask(&self) -> u32 {
+		*self as u32
+	}
+
+	pub fn short_name(&self) -> &'static str {
+		match self {
+			Speaker::FrontLeft => "FL",
+			Speaker::FrontRight => "FR",
+			Speaker::FrontCenter => "FC",
+			Speaker::LowFrequency => "LFE",
+			Speaker::BackLeft => "BL",
+			Speaker::BackRight => "BR",
+			Speaker::FrontLeftOfCenter => "FLC",
+			Speaker::FrontRightOfCenter => "FRC",
+			Speaker::BackCenter => "BC",
+			Speaker::SideLeft => "SL",
+			Speaker::SideRight => "SR",
+			Speaker::TopCenter => "TC",
+			Speaker::TopFrontLeft => "TFL",
+			Speaker::TopFrontCenter => "TFC",
+			Speaker::TopFrontRight => "TFR",
+			Speaker::TopBackLeft => "TBL",
+			Speaker::TopBackCenter => "TBC",
+			Speaker::TopBackRight => "TBR",
+		}
+	}
+}
+
+// a channel count plus, when known, the speaker of each channel in mask bit order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelLayout {
+	mask: u32,
+	channels: u8,
+}
+
+impl ChannelLayout {
+	pub const MONO: ChannelLayout = ChannelLayout::from_mask(0x4);
+	pub const STEREO: ChannelLayout = ChannelLayout::from_mask(0x3);
+	pub const SURROUND_2_1: ChannelLayout = ChannelLayout::from_mask(0xB);
+	pub const SURROUND_5_1: ChannelLayout = ChannelLayout::from_mask(0x3F);
+	pub const SURROUND_7_1: ChannelLayout = ChannelLayout::from_mask(0x63F);
+
+	pub const fn from_mask(mask: u32) -> Self {
+		Self { mask, channels: mask.count_ones() as u8 }
+	}
+
+	// channels without assigned speakers
+	pub const fn unknown(channels: u8) -> Self {
+		Self { mask: 0, channels }
+	}
+
+	pub fn default_for(channels: u8) -> Self {
+		match channels {
+			1 => Self::MONO,
+			2 => Self::STEREO,
+			3 => Self::SURROUND_2_1,
+			6 => Self::SURROUND_5_1,
+			8 => Self::SURROUND_7_1,
+			_ => Self::unknown(channels),
+		}
+	}
+
+	// "mono", "stereo", "2.1", "5.1", "7.1" or a plain channel count
+	pub fn parse(value: &str) -> Result<Self> {
+		match value.to_lowercase().as_str() {
+			"mono" => Ok(Self::MONO),
+			"stereo" => Ok(Self::STEREO),
+			"2.1" => Ok(Self::SURROUND_2_1),
+			"5.1" => Ok(Self::SURROUND_5_1),
+			"7.1" => Ok(Self::SURROUND_7_1),
+			other => match other.parse::<u8>() {
+				Ok(channels) if channels > 0 => Ok(Self::default_for(channels)),
+				_ => Err(Error::invalid_data(format!("invalid channel layout '{}'", value))),
+			},
+		}
+	}
+
+	pub fn mask(&self) -> u32 {
+		self.mask
+	}
+
+	pub fn channels(&self) -> u8 {
+		self.channels
+	}
+
+	pub fn is_unknown(&self) -> bool {
+		self.mask == 0
+	}
+
+	pub fn speakers(&self) -> Vec<Speaker> {
+		Speaker::ALL.iter().copied().filter(|s| self.mask & s.mask() != 0).collect()
+	}
+
+	pub fn contains(&self, speaker: Speaker) -> bool {
+		self.mask & speaker.mask() != 0
+	}
+
+	pub fn index_of(&self, speaker: Speaker) -> Option<usize> {
+		self.speakers().iter().position(|&s| s == speaker)
+	}
+
+	pub fn name(&self) -> String {
+		match *self {
+			Self::MONO => "mono".to_string(),
+			Self::STEREO => "stereo".to_string(),
+			Self::SURROUND_2_1 => "2.1".to_string(),
+			Self::SURROUND_5_1 => "5.1".to_string(),
+			Self::SURROUND_7_1 => "7.1".to_string(),
+			_ if self.is_unknown() => format!("{} channels", self.channels),
+			_ => self.speakers().iter().map(|s| s.short_name()).collect::<Vec<_>>().join("+"),
+		}
+	}
+}
+
+impl Default for ChannelLayout {
+	fn default() -> Self {
+		Self::STEREO
+	}
+}
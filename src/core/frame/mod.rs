@@ -1,9 +1,12 @@
 mod audio;
+mod layout;
+pub mod sample;
 mod subtitle;
 mod video;
 
 use crate::core::time::Time;
 pub use audio::*;
+pub use layout::*;
 pub use subtitle::*;
 pub use video::*;
 
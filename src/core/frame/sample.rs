@@ -0,0 +1,75 @@
+use super::audio::AudioFormat;
+use crate::io::{Error, Result};
+
+// interleaved pcm bytes as floats in [-1, 1], whatever the sample size
+pub fn to_f32(data: &[u8], format: AudioFormat) -> Result<Vec<f32>> {
+	match format {
+		AudioFormat::PCM16 => from_pcm16(data),
+		AudioFormat::PCM24 => from_pcm24(data),
+		AudioFormat::PCM32 => from_pcm32(data),
+		AudioFormat::PCM32Int => from_pcm32_int(data),
+		_ => Err(Error::invalid_data(format!("{:?} samples are not pcm", format))),
+	}
+}
+
+pub fn from_f32(samples: &[f32], format: AudioFormat) -> Result<Vec<u8>> {
+	match format {
+		AudioFormat::PCM16 => Ok(to_pcm16(samples)),
+		AudioFormat::PCM24 => Ok(to_pcm24(samples)),
+		AudioFormat::PCM32 => Ok(to_pcm32(samples)),
+		AudioFormat::PCM32Int => Ok(to_pcm32_int(samples)),
+		_ => Err(Error::invalid_data(format!("{:?} samples are not pcm", format))),
+	}
+}
+
+fn from_pcm16(data: &[u8]) -> Result<Vec<f32>> {
+	if !data.len().is_multiple_of(2) {
+		return Err(Error::invalid_data("invalid pcm16 length"));
+	}
+	let values = data.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]]));
+	Ok(values.map(|value| value as f32 / 32768.0).collect())
+}
+
+fn from_pcm24(data: &[u8]) -> Result<Vec<f32>> {
+	if !data.len().is_multiple_of(3) {
+		return Err(Error::invalid_data("invalid pcm24 length"));
+	}
+	let values = data.chunks_exact(3).map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8);
+	Ok(values.map(|value| value as f32 / 8388608.0).collect())
+}
+
+fn from_pcm32(data: &[u8]) -> Result<Vec<f32>> {
+	if !data.len().is_multiple_of(4) {
+		return Err(Error::invalid_data("invalid pcm32 length"));
+	}
+	let values = data.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+	Ok(values.map(|value| value.clamp(-1.0, 1.0)).collect())
+}
+
+fn from_pcm32_int(data: &[u8]) -> Result<Vec<f32>> {
+	if !data.len().is_multiple_of(4) {
+		return Err(Error::invalid_data("invalid pcm32 length"));
+	}
+	let values = data.chunks_exact(4).map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+	Ok(values.map(|value| (value as f64 / 2147483648.0) as f32).collect())
+}
+
+fn to_pcm16(samples: &[f32]) -> Vec<u8> {
+	let values = samples.iter().map(|s| (s * 32768.0).clamp(-32768.0, 32767.0) as i16);
+	values.flat_map(i16::to_le_bytes).collect()
+}
+
+fn to_pcm24(samples: &[f32]) -> Vec<u8> {
+	let values = samples.iter().map(|s| (s * 8388608.0).clamp(-8388608.0, 8388607.0) as i32);
+	values.flat_map(|value| value.to_le_bytes().into_iter().take(3)).collect()
+}
+
+fn to_pcm32(samples: &[f32]) -> Vec<u8> {
+	samples.iter().flat_map(|s| s.clamp(-1.0, 1.0).to_le_bytes()).collect()
+}
+
+fn to_pcm32_int(samples: &[f32]) -> Vec<u8> {
+	let values =
+		samples.iter().map(|&s| (s as f64 * 2147483648.0).clamp(-2147483648.0, 2147483647.0));
+	values.flat_map(|value| (value as i32).to_le_bytes()).collect()
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+// container independent tags, keyed by lowercase names like "artist" or "title"
+#[derive(Debug, Clone)]
+pub struct Metadata {
+	pub fields: HashMap<String, String>,
+	pub broadcast: Option<Broadcast>,
+	// iXML document of field recorders, kept as written
+	pub ixml: Option<String>,
+	pub markers: Vec<Marker>,
+	pub sampler: Option<Sampler>,
+}
+
+// the EBU Broadcast Wave extension (bext)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Broadcast {
+	pub description: String,
+	pub originator: String,
+	pub originator_reference: String,
+	// "yyyy-mm-dd" and "hh:mm:ss"
+	pub origination_date: String,
+	pub origination_time: String,
+	// samples since midnight at the first sample
+	pub time_reference: u64,
+	pub version: u16,
+	pub umid: Vec<u8>,
+	// version 2 and up, in hundredths of LU, LUFS or dBTP
+	pub loudness_value: i16,
+	pub loudness_range: i16,
+	pub max_true_peak_level: i16,
+	pub max_momentary_loudness: i16,
+	pub max_short_term_loudness: i16,
+	pub coding_history: String,
+}
+
+// a cue point, `position` in samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+	pub id: u32,
+	pub position: u64,
+	pub label: Option<String>,
+}
+
+// sampler instrument data (smpl), loops are in samples
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sampler {
+	pub manufacturer: u32,
+	pub product: u32,
+	pub sample_period: u32,
+	pub midi_unity_note: u32,
+	pub midi_pitch_fraction: u32,
+	pub smpte_format: u32,
+	pub smpte_offset: u32,
+	pub loops: Vec<SampleLoop>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleLoop {
+	pub id: u32,
+	pub kind: u32,
+	pub start: u64,
+	pub end: u64,
+	pub fraction: u32,
+	pub play_count: u32,
+}
+
+impl Metadata {
+	pub fn new() -> Self {
+		Self { fields: HashMap::new(), broadcast: None, ixml: None, markers: Vec::new(), sampler: None }
+	}
+
+	pub fn set(&mut self, key: &str, value: String) {
+		self.fields.insert(key.to_string(), value);
+	}
+
+	pub fn remove(&mut self, key: &str) {
+		self.fields.remove(key);
+	}
+
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.fields.get(key).map(|s| s.as_str())
+	}
+
+	pub fn artist(&self) -> Option<&str> {
+		self.get("artist")
+	}
+
+	pub fn title(&self) -> Option<&str> {
+		self.get("title")
+	}
+
+	pub fn set_artist(&mut self, artist: String) {
+		self.set("artist", artist);
+	}
+
+	pub fn set_title(&mut self, title: String) {
+		self.set("title", title);
+	}
+
+	pub fn all_fields(&self) -> &HashMap<String, String> {
+		&self.fields
+	}
+
+	// no tags, the chunk data beside them is not looked at
+	pub fn is_empty(&self) -> bool {
+		self.fields.is_empty()
+	}
+
+	// the marker with `id`, created at 0 when a label arrives before its cue point
+	pub fn marker_mut(&mut self, id: u32) -> &mut Marker {
+		match self.markers.iter().position(|marker| marker.id == id) {
+			Some(index) => &mut self.markers[index],
+			None => {
+				self.markers.push(Marker { id, position: 0, label: None });
+				self.markers.last_mut().unwrap()
+			}
+		}
+	}
+
+	// sample positions for output that keeps input samples `start` up to `end` and runs at
+	// `to_rate`, markers and loops that were cut off are dropped, loops running past the end
+	// stop at its last sample
+	pub fn retime(&mut self, start: u64, end: Option<u64>, from_rate: u32, to_rate: u32) {
+		// positions come from the file, anything too large for the new rate stays at the maximum
+		let scale = |position: u64| {
+			let scaled = position as u128 * to_rate as u128 / std::cmp::max(1, from_rate) as u128;
+			u64::try_from(scaled).unwrap_or(u64::MAX)
+		};
+		let end = end.unwrap_or(u64::MAX);
+
+		if let Some(broadcast) = &mut self.broadcast {
+			broadcast.time_reference = scale(broadcast.time_reference.saturating_add(start));
+		}
+		// a cue point may sit right after the last sample
+		self.markers.retain(|marker| marker.position >= start && marker.position <= end);
+		for marker in &mut self.markers {
+			marker.position = scale(marker.position - start);
+		}
+		if let Some(sampler) = &mut self.sampler {
+			sampler.loops.retain(|lp| lp.start >= start && lp.start < end);
+			for lp in &mut sampler.loops {
+				let last = std::cmp::min(lp.end, end - 1);
+				(lp.start, lp.end) = (scale(lp.start - start), scale(last.saturating_sub(start)));
+			}
+		}
+	}
+}
+
+impl Default for Metadata {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_retime_saturates() {
+		let mut metadata = Metadata::new();
+		metadata.broadcast = Some(Broadcast { time_reference: u64::MAX - 10, ..Default::default() });
+		metadata.marker_mut(1).position = u64::MAX;
+		metadata.retime(100, None, 48000, 96000);
+
+		assert_eq!(metadata.broadcast.map(|broadcast| broadcast.time_reference), Some(u64::MAX));
+		assert_eq!(metadata.markers[0].position, u64::MAX);
+	}
+
+	#[test]
+	fn test_retime_follows_the_cut() {
+		let mut metadata = Metadata::new();
+		metadata.broadcast = Some(Broadcast { time_reference: 48000, ..Default::default() });
+		metadata.marker_mut(2).label = Some("chorus".to_string());
+		metadata.marker_mut(1).position = 1000;
+		metadata.marker_mut(2).position = 96000;
+
+		metadata.marker_mut(3).position = 200000;
+		let loops = [(1, 2000, 4000), (2, 60000, 80000), (3, 120000, 180000), (4, 150000, 160000)];
+		metadata.sampler = Some(Sampler {
+			loops: loops
+				.iter()
+				.map(|&(id, start, end)| SampleLoop { id, kind: 0, start, end, fraction: 0, play_count: 0 })
+				.collect(),
+			..Default::default()
+		});
+
+		// seconds 1 to 3 of the input
+		metadata.retime(48000, Some(144000), 48000, 24000);
+		assert_eq!(metadata.broadcast.map(|b| b.time_reference), Some(48000));
+		assert_eq!(metadata.markers.len(), 1);
+		assert_eq!(metadata.markers[0].position, 24000);
+		assert_eq!(metadata.markers[0].label.as_deref(), Some("chorus"));
+
+		let loops: Vec<_> =
+			metadata.sampler.unwrap().loops.iter().map(|lp| (lp.id, lp.start, lp.end)).collect();
+		assert_eq!(loops, vec![(2, 6000, 16000), (3, 36000, 47999)]);
+	}
+}
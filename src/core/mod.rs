@@ -1,8 +1,11 @@
 pub mod compatible;
 pub mod frame;
+pub mod metadata;
 pub mod packet;
+pub mod parameters;
+pub mod registry;
 pub mod stream;
 pub mod time;
 pub mod traits;
 
-pub use traits::{Decoder, Demuxer, Encoder, Muxer, Transform};
+pub use traits::{Decoder, Demuxer, Encoder, Muxer, Receive, SeekFlags, Transform};
@@ -5,6 +5,8 @@ pub struct Packet {
 	pub data: Vec<u8>,
 	pub pts: i64,
 	pub dts: i64,
+	// samples the packet decodes to, 0 when it follows from the size
+	pub duration: i64,
 	pub time: Time,
 	pub stream_index: usize,
 	pub keyframe: bool,
@@ -13,7 +15,7 @@ pub struct Packet {
 
 impl Packet {
 	pub fn new(data: Vec<u8>, stream_index: usize, time: Time) -> Self {
-		Self { data, pts: 0, dts: 0, time, stream_index, keyframe: false, discard: false }
+		Self { data, pts: 0, dts: 0, duration: 0, time, stream_index, keyframe: false, discard: false }
 	}
 
 	pub fn with_pts(mut self, pts: i64) -> Self {
@@ -26,6 +28,11 @@ impl Packet {
 		self
 	}
 
+	pub fn with_duration(mut self, duration: i64) -> Self {
+		self.duration = duration;
+		self
+	}
+
 	pub fn with_keyframe(mut self, keyframe: bool) -> Self {
 		self.keyframe = keyframe;
 		self
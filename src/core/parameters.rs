@@ -0,0 +1,113 @@
+use crate::core::frame::ChannelLayout;
+use crate::core::stream::StreamKind;
+use crate::core::time::Time;
+
+// what a demuxer knows about the coded audio, 0 stands for unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioParameters {
+	pub sample_rate: u32,
+	pub layout: ChannelLayout,
+	pub bits_per_sample: u16,
+	// bits of each sample that carry signal, 0 when all of them do
+	pub valid_bits: u16,
+	// bytes per coded block, one frame of samples for pcm
+	pub block_align: u16,
+	// samples in each coded block, 0 for pcm
+	pub frame_size: u32,
+	pub bit_rate: u32,
+}
+
+impl AudioParameters {
+	pub fn new(sample_rate: u32, layout: ChannelLayout, bits_per_sample: u16) -> Self {
+		let block_align = layout.channels() as u16 * bits_per_sample.div_ceil(8);
+		let bit_rate =
+			sample_rate.saturating_mul(layout.channels() as u32).saturating_mul(bits_per_sample as u32);
+		Self {
+			sample_rate,
+			layout,
+			bits_per_sample,
+			valid_bits: 0,
+			block_align,
+			frame_size: 0,
+			bit_rate,
+		}
+	}
+
+	pub fn channels(&self) -> u8 {
+		self.layout.channels()
+	}
+
+	pub fn with_valid_bits(mut self, valid_bits: u16) -> Self {
+		self.valid_bits = valid_bits;
+		self
+	}
+
+	pub fn with_block_align(mut self, block_align: u16) -> Self {
+		self.block_align = block_align;
+		self
+	}
+
+	pub fn with_frame_size(mut self, frame_size: u32) -> Self {
+		self.frame_size = frame_size;
+		self
+	}
+
+	pub fn with_bit_rate(mut self, bit_rate: u32) -> Self {
+		self.bit_rate = bit_rate;
+		self
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VideoParameters {
+	pub width: u32,
+	pub height: u32,
+	pub pixel_format: Option<String>,
+	pub frame_rate: Option<Time>,
+	pub bit_rate: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SubtitleParameters {
+	pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecParameters {
+	Audio(AudioParameters),
+	Video(VideoParameters),
+	Subtitle(SubtitleParameters),
+}
+
+impl CodecParameters {
+	// nothing known yet, shaped for `kind`
+	pub fn unknown(kind: StreamKind) -> Self {
+		match kind {
+			StreamKind::Audio => Self::Audio(AudioParameters::new(0, ChannelLayout::unknown(0), 0)),
+			StreamKind::Video => Self::Video(VideoParameters::default()),
+			StreamKind::Subtitle => Self::Subtitle(SubtitleParameters::default()),
+		}
+	}
+
+	pub fn kind(&self) -> StreamKind {
+		match self {
+			Self::Audio(_) => StreamKind::Audio,
+			Self::Video(_) => StreamKind::Video,
+			Self::Subtitle(_) => StreamKind::Subtitle,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_audio_parameters_derive_block_align() {
+		let audio = AudioParameters::new(48000, ChannelLayout::STEREO, 24);
+		assert_eq!((audio.channels(), audio.block_align, audio.bit_rate), (2, 6, 2_304_000));
+
+		let unknown = CodecParameters::unknown(StreamKind::Video);
+		assert_eq!(unknown.kind(), StreamKind::Video);
+	}
+}
@@ -0,0 +1,231 @@
+use crate::container::probe::{ProbeFn, Prober};
+use crate::core::frame::ChannelLayout;
+use crate::core::stream::{Stream, StreamKind};
+use crate::core::{Decoder, Demuxer, Encoder, Muxer};
+use crate::io::stdio::{StdioSink, StdioSource};
+use crate::io::{Error, Result};
+use crate::{codecs, container};
+
+// sample range a demuxer should deliver, data outside of it is skipped without decoding
+#[derive(Debug, Clone, Default)]
+pub struct DemuxerOptions {
+	pub sample_range: Option<(u64, Option<u64>)>,
+	// what headerless input holds, containers with a header ignore these
+	pub codec: Option<String>,
+	pub sample_rate: Option<u32>,
+	pub layout: Option<ChannelLayout>,
+}
+
+pub type DemuxerFactory = fn(StdioSource, &DemuxerOptions) -> Result<Box<dyn Demuxer>>;
+// `streams` describe what the muxer is going to be fed, packets refer to them by index
+pub type MuxerFactory = fn(StdioSink, &[Stream]) -> Result<Box<dyn Muxer>>;
+pub type DecoderFactory = fn(&Stream) -> Result<Box<dyn Decoder>>;
+pub type EncoderFactory = fn(&Stream) -> Result<Box<dyn Encoder>>;
+
+pub struct ContainerEntry {
+	pub name: &'static str,
+	pub extensions: Vec<&'static str>,
+	pub codecs: Vec<&'static str>,
+	pub probe: Option<ProbeFn>,
+	pub demuxer: Option<DemuxerFactory>,
+	pub muxer: Option<MuxerFactory>,
+}
+
+impl ContainerEntry {
+	pub fn new(name: &'static str) -> Self {
+		Self {
+			name,
+			extensions: vec![name],
+			codecs: Vec::new(),
+			probe: None,
+			demuxer: None,
+			muxer: None,
+		}
+	}
+
+	pub fn with_extensions(mut self, extensions: &[&'static str]) -> Self {
+		self.extensions.extend_from_slice(extensions);
+		self
+	}
+
+	pub fn with_codecs(mut self, codecs: &[&'static str]) -> Self {
+		self.codecs.extend_from_slice(codecs);
+		self
+	}
+
+	pub fn with_probe(mut self, probe: ProbeFn) -> Self {
+		self.probe = Some(probe);
+		self
+	}
+
+	pub fn with_demuxer(mut self, demuxer: DemuxerFactory) -> Self {
+		self.demuxer = Some(demuxer);
+		self
+	}
+
+	pub fn with_muxer(mut self, muxer: MuxerFactory) -> Self {
+		self.muxer = Some(muxer);
+		self
+	}
+
+	pub fn matches(&self, format: &str) -> bool {
+		self.name == format || self.extensions.contains(&format)
+	}
+}
+
+pub struct CodecEntry {
+	pub name: &'static str,
+	pub kind: StreamKind,
+	pub decoder: Option<DecoderFactory>,
+	pub encoder: Option<EncoderFactory>,
+}
+
+impl CodecEntry {
+	pub fn new(name: &'static str, kind: StreamKind) -> Self {
+		Self { name, kind, decoder: None, encoder: None }
+	}
+
+	pub fn with_decoder(mut self, decoder: DecoderFactory) -> Self {
+		self.decoder = Some(decoder);
+		self
+	}
+
+	pub fn with_encoder(mut self, encoder: EncoderFactory) -> Self {
+		self.encoder = Some(encoder);
+		self
+	}
+}
+
+// everything the crate can read and write, containers and codecs register themselves here
+pub struct Registry {
+	containers: Vec<ContainerEntry>,
+	codecs: Vec<CodecEntry>,
+}
+
+impl Registry {
+	pub fn empty() -> Self {
+		Self { containers: Vec::new(), codecs: Vec::new() }
+	}
+
+	pub fn register_container(&mut self, entry: ContainerEntry) {
+		self.containers.push(entry);
+	}
+
+	pub fn register_codec(&mut self, entry: CodecEntry) {
+		self.codecs.push(entry);
+	}
+
+	pub fn containers(&self) -> &[ContainerEntry] {
+		&self.containers
+	}
+
+	pub fn codecs(&self) -> &[CodecEntry] {
+		&self.codecs
+	}
+
+	// by name or by file extension
+	pub fn container(&self, format: &str) -> Option<&ContainerEntry> {
+		self.containers.iter().find(|entry| entry.matches(format))
+	}
+
+	pub fn codec(&self, name: &str) -> Option<&CodecEntry> {
+		self.codecs.iter().find(|entry| entry.name == name)
+	}
+
+	pub fn prober(&self) -> Prober {
+		let mut prober = Prober::empty();
+		for entry in &self.containers {
+			if let Some(probe) = entry.probe {
+				prober.register(entry.name, probe);
+			}
+		}
+		prober
+	}
+
+	pub fn open_demuxer(
+		&self,
+		format: &str,
+		source: StdioSource,
+		options: &DemuxerOptions,
+	) -> Result<Box<dyn Demuxer>> {
+		match self.container(format).and_then(|entry| entry.demuxer) {
+			Some(demuxer) => demuxer(source, options),
+			None => Err(Error::invalid_data(format!("reading '{}' is not supported", format))),
+		}
+	}
+
+	pub fn create_muxer(
+		&self,
+		format: &str,
+		sink: StdioSink,
+		streams: &[Stream],
+	) -> Result<Box<dyn Muxer>> {
+		let Some(entry) = self.container(format) else {
+			return Err(Error::invalid_data(format!("'{}' is not supported", format)));
+		};
+		if let Some(stream) =
+			streams.iter().find(|stream| !entry.codecs.contains(&stream.codec.as_str()))
+		{
+			let message = format!("codec '{}' not supported in '{}'", stream.codec, entry.name);
+			return Err(Error::invalid_data(message));
+		}
+		match entry.muxer {
+			Some(muxer) => muxer(sink, streams),
+			None => Err(Error::invalid_data(format!("writing '{}' is not supported", format))),
+		}
+	}
+
+	pub fn create_decoder(&self, stream: &Stream) -> Result<Box<dyn Decoder>> {
+		match self.codec(&stream.codec).and_then(|entry| entry.decoder) {
+			Some(decoder) => decoder(stream),
+			None => Err(Error::invalid_data(format!("no decoder for '{}'", stream.codec))),
+		}
+	}
+
+	pub fn create_encoder(&self, stream: &Stream) -> Result<Box<dyn Encoder>> {
+		match self.codec(&stream.codec).and_then(|entry| entry.encoder) {
+			Some(encoder) => encoder(stream),
+			None => Err(Error::invalid_data(format!("no encoder for '{}'", stream.codec))),
+		}
+	}
+}
+
+impl Default for Registry {
+	fn default() -> Self {
+		let mut registry = Self::empty();
+		container::register(&mut registry);
+		codecs::register(&mut registry);
+		registry
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::compatible::Compatible;
+	use crate::core::frame::ChannelLayout;
+	use crate::core::parameters::{AudioParameters, CodecParameters};
+	use crate::core::time::Time;
+
+	#[test]
+	fn test_capabilities_follow_registrations() {
+		let registry = Registry::default();
+		assert_eq!(registry.container(container::PCM).map(|entry| entry.name), Some(container::RAW));
+		assert!(registry.container(container::MKV).is_some_and(|entry| entry.demuxer.is_none()));
+
+		let compat = Compatible::from_registry(&registry);
+		assert!(compat.assert_audio_supported(container::WAV, codecs::audio::PCM_S24LE).is_ok());
+		assert!(compat.assert_audio_supported(container::WAV, codecs::audio::MP3).is_err());
+		assert!(compat.assert_container_supported(container::MKV).is_err());
+
+		let time = Time::new(1, 8000);
+		let audio = AudioParameters::new(8000, ChannelLayout::MONO, 32);
+		let stream = Stream::new(0, 0, StreamKind::Audio, codecs::audio::PCM_F32LE.into(), time)
+			.with_parameters(CodecParameters::Audio(audio));
+		assert!(registry.create_decoder(&stream).is_ok());
+		assert!(registry.create_encoder(&stream).is_ok());
+
+		let stream = Stream::new(0, 0, StreamKind::Audio, codecs::audio::MP3.into(), time);
+		assert!(registry.create_decoder(&stream).is_err());
+	}
+}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use crate::core::parameters::{AudioParameters, CodecParameters, VideoParameters};
 use crate::core::time::Time;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -17,11 +18,22 @@ pub struct Stream {
 	pub codec: String,
 	pub time: Time,
 	pub codec_private: Vec<u8>,
+	pub parameters: CodecParameters,
+	pub duration: Option<i64>,
 }
 
 impl Stream {
 	pub fn new(id: u32, index: usize, kind: StreamKind, codec: String, time: Time) -> Self {
-		Self { id, index, kind, codec, time, codec_private: Vec::new() }
+		Self {
+			id,
+			index,
+			kind,
+			codec,
+			time,
+			codec_private: Vec::new(),
+			parameters: CodecParameters::unknown(kind),
+			duration: None,
+		}
 	}
 
 	pub fn with_codec_private(mut self, codec_private: Vec<u8>) -> Self {
@@ -29,6 +41,42 @@ impl Stream {
 		self
 	}
 
+	pub fn with_parameters(mut self, parameters: CodecParameters) -> Self {
+		self.parameters = parameters;
+		self
+	}
+
+	pub fn audio_parameters(&self) -> Option<&AudioParameters> {
+		match &self.parameters {
+			CodecParameters::Audio(audio) => Some(audio),
+			_ => None,
+		}
+	}
+
+	pub fn audio_parameters_mut(&mut self) -> Option<&mut AudioParameters> {
+		match &mut self.parameters {
+			CodecParameters::Audio(audio) => Some(audio),
+			_ => None,
+		}
+	}
+
+	pub fn video_parameters(&self) -> Option<&VideoParameters> {
+		match &self.parameters {
+			CodecParameters::Video(video) => Some(video),
+			_ => None,
+		}
+	}
+
+	// in `time` units
+	pub fn with_duration(mut self, duration: i64) -> Self {
+		self.duration = Some(duration);
+		self
+	}
+
+	pub fn duration_seconds(&self) -> Option<f64> {
+		self.duration.map(|duration| self.time.to_seconds(duration))
+	}
+
 	#[inline(always)]
 	pub fn is_audio(&self) -> bool {
 		matches!(self.kind, StreamKind::Audio)
@@ -94,14 +142,24 @@ impl Streams {
 
 impl Display for Stream {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "stream {} ({:?}) [{}]", self.index, self.kind, self.codec)
+		write!(f, "stream {} ({:?}) [{}]", self.index, self.kind, self.codec)?;
+		match &self.parameters {
+			CodecParameters::Audio(audio) if audio.channels() > 0 => {
+				write!(f, " {} Hz {}", audio.sample_rate, audio.layout.name())?;
+			}
+			CodecParameters::Video(video) if video.width > 0 => {
+				write!(f, " {}x{}", video.width, video.height)?;
+			}
+			_ => {}
+		}
+		Ok(())
 	}
 }
 
 impl Display for Streams {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		for stream in &self.inner {
-			write!(f, "{}\n", stream)?;
+			writeln!(f, "{}", stream)?;
 		}
 		Ok(())
 	}
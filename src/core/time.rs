@@ -1,3 +1,5 @@
+use crate::io::{Error, Result};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Time {
 	pub num: u32,
@@ -34,3 +36,81 @@ impl Time {
 		Time::new(self.num / g, self.den / g)
 	}
 }
+
+// converts `pts` from one time base to another without intermediate rounding
+pub fn rescale(pts: i64, from: Time, to: Time) -> i64 {
+	(pts as i128 * from.num as i128 * to.den as i128 / (from.den as i128 * to.num as i128)) as i64
+}
+
+// a position given as a duration or as an exact sample count
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timestamp {
+	Seconds(f64),
+	Samples(u64),
+}
+
+impl Timestamp {
+	// "01:02:03.5", "02:03.5", "90s", "1500ms", "44100samples" or plain seconds
+	pub fn parse(value: &str) -> Result<Self> {
+		let lower = value.trim().to_lowercase();
+		let invalid = || Error::invalid_data(format!("invalid time '{}'", value));
+
+		if let Some(samples) = lower.strip_suffix("samples").or_else(|| lower.strip_suffix("sample")) {
+			let samples = samples.trim().parse::<u64>().map_err(|_| invalid())?;
+			return Ok(Timestamp::Samples(samples));
+		}
+
+		let seconds = if let Some(ms) = lower.strip_suffix("ms") {
+			ms.trim().parse::<f64>().map_err(|_| invalid())? / 1000.0
+		} else if let Some(seconds) = lower.strip_suffix('s') {
+			seconds.trim().parse::<f64>().map_err(|_| invalid())?
+		} else if lower.contains(':') {
+			let parts: Vec<&str> = lower.split(':').collect();
+			if parts.len() > 3 {
+				return Err(invalid());
+			}
+			let mut seconds = 0.0;
+			for (index, part) in parts.iter().enumerate() {
+				let value = part.parse::<f64>().map_err(|_| invalid())?;
+				let last = index + 1 == parts.len();
+				if !last && (value.fract() != 0.0 || part.is_empty()) {
+					return Err(invalid());
+				}
+				seconds = seconds * 60.0 + value;
+			}
+			seconds
+		} else {
+			lower.parse::<f64>().map_err(|_| invalid())?
+		};
+
+		if !seconds.is_finite() || seconds < 0.0 {
+			return Err(invalid());
+		}
+		Ok(Timestamp::Seconds(seconds))
+	}
+
+	pub fn to_samples(&self, sample_rate: u32) -> u64 {
+		match *self {
+			Timestamp::Seconds(seconds) => (seconds * sample_rate as f64).round() as u64,
+			Timestamp::Samples(samples) => samples,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_timestamp_syntax() {
+		assert_eq!(Timestamp::parse("00:01:02.5").unwrap(), Timestamp::Seconds(62.5));
+		assert_eq!(Timestamp::parse("1:30").unwrap(), Timestamp::Seconds(90.0));
+		assert_eq!(Timestamp::parse("10s").unwrap(), Timestamp::Seconds(10.0));
+		assert_eq!(Timestamp::parse("250ms").unwrap(), Timestamp::Seconds(0.25));
+		assert_eq!(Timestamp::parse("7").unwrap(), Timestamp::Seconds(7.0));
+		assert_eq!(Timestamp::parse("44100samples").unwrap(), Timestamp::Samples(44100));
+		assert_eq!(Timestamp::parse("0.5").unwrap().to_samples(48000), 24000);
+		assert!(Timestamp::parse("-1s").is_err());
+		assert!(Timestamp::parse("1.5:00").is_err());
+	}
+}
@@ -3,7 +3,27 @@ use crate::{
 	io::Result,
 };
 
+// what a codec has to offer when asked for output
+#[derive(Debug)]
+pub enum Receive<T> {
+	Ready(T),
+	// everything sent so far has been consumed, send more input
+	NeedMore,
+	// drained after the end of input, nothing else will come out
+	EndOfStream,
+}
+
 pub trait Decoder {
-	fn decode(&mut self, packet: Packet) -> Result<Option<Frame>>;
-	fn flush(&mut self) -> Result<Option<Frame>>;
+	// `None` marks the end of input, buffered frames can then be drained
+	fn send_packet(&mut self, packet: Option<Packet>) -> Result<()>;
+	fn receive_frame(&mut self) -> Result<Receive<Frame>>;
+
+	// every frame that is ready right now
+	fn receive_frames(&mut self) -> Result<Vec<Frame>> {
+		let mut frames = Vec::new();
+		while let Receive::Ready(frame) = self.receive_frame()? {
+			frames.push(frame);
+		}
+		Ok(frames)
+	}
 }
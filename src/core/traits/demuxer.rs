@@ -1,11 +1,33 @@
+use crate::core::metadata::Metadata;
 use crate::core::packet::Packet;
 use crate::core::stream::Streams;
-use crate::io::Result;
+use crate::io::{Error, Result};
+
+// where a seek may land relative to the requested timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeekFlags {
+	// the last keyframe at or before it, decoding from there reaches it
+	#[default]
+	KeyframeBefore,
+	// the closest position the container can start reading from
+	Any,
+	// exactly on it, as far as the container allows
+	Exact,
+}
 
 pub trait Demuxer {
 	fn streams(&self) -> &Streams;
 	fn read_packet(&mut self) -> Result<Option<Packet>>;
 
+	fn metadata(&self) -> Option<&Metadata> {
+		None
+	}
+
+	// `timestamp` is in the time base of `stream_index`, returns the pts the next packet starts at
+	fn seek(&mut self, _stream_index: usize, _timestamp: i64, _flags: SeekFlags) -> Result<i64> {
+		Err(Error::not_seekable())
+	}
+
 	fn read_audio_packet(&mut self) -> Result<Option<Packet>> {
 		while let Some(packet) = self.read_packet()? {
 			let stream = self.streams().get(packet.stream_index);
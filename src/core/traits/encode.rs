@@ -1,8 +1,19 @@
+use super::decode::Receive;
 use crate::core::frame::Frame;
 use crate::core::packet::Packet;
 use crate::io::Result;
 
 pub trait Encoder {
-	fn encode(&mut self, frame: Frame) -> Result<Option<Packet>>;
-	fn flush(&mut self) -> Result<Option<Packet>>;
+	// `None` marks the end of input, buffered packets can then be drained
+	fn send_frame(&mut self, frame: Option<Frame>) -> Result<()>;
+	fn receive_packet(&mut self) -> Result<Receive<Packet>>;
+
+	// every packet that is ready right now
+	fn receive_packets(&mut self) -> Result<Vec<Packet>> {
+		let mut packets = Vec::new();
+		while let Receive::Ready(packet) = self.receive_packet()? {
+			packets.push(packet);
+		}
+		Ok(packets)
+	}
 }
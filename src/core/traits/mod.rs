@@ -4,8 +4,8 @@ pub mod encode;
 pub mod muxer;
 pub mod transform;
 
-pub use decode::Decoder;
-pub use demuxer::Demuxer;
+pub use decode::{Decoder, Receive};
+pub use demuxer::{Demuxer, SeekFlags};
 pub use encode::Encoder;
 pub use muxer::Muxer;
 pub use transform::Transform;
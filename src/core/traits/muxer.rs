@@ -1,3 +1,4 @@
+use crate::core::metadata::Metadata;
 use crate::core::packet::Packet;
 use crate::core::stream::Streams;
 use crate::io::Result;
@@ -6,4 +7,7 @@ pub trait Muxer {
 	fn streams(&self) -> &Streams;
 	fn write(&mut self, packet: Packet) -> Result<()>;
 	fn finalize(&mut self) -> Result<()>;
+
+	// containers without tags drop it
+	fn set_metadata(&mut self, _metadata: Metadata) {}
 }
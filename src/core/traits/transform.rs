@@ -1,6 +1,19 @@
+use std::time::Duration;
+
 use crate::{core::frame::Frame, io::Result};
 
 pub trait Transform: Send {
-	fn apply(&mut self, frame: Frame) -> Result<Frame>;
+	// zero or more frames for every input frame, buffering transforms may hold output back
+	fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>>;
 	fn name(&self) -> &'static str;
+
+	// how much input is held back before the matching output comes out
+	fn latency(&self) -> Duration {
+		Duration::ZERO
+	}
+
+	// end of stream: releases buffered frames, called until it returns none
+	fn flush(&mut self) -> Result<Vec<Frame>> {
+		Ok(Vec::new())
+	}
 }
@@ -14,25 +14,25 @@ pub fn mapper_error<T>(result: std::io::Result<T>, path: &str) -> io::Result<T>
 		Err(error) => match error.kind() {
 			std::io::ErrorKind::AlreadyExists => {
 				let message = format!("'{}' already exists", path);
-				return Err(io::Error::with_message(io::ErrorKind::AlreadyExists, message));
+				Err(io::Error::with_message(io::ErrorKind::AlreadyExists, message))
 			}
 			std::io::ErrorKind::PermissionDenied => {
 				let message = format!("permission denied for '{}'", path);
-				return Err(io::Error::with_message(io::ErrorKind::PermissionDenied, message));
+				Err(io::Error::with_message(io::ErrorKind::PermissionDenied, message))
 			}
 			std::io::ErrorKind::NotFound => {
 				let message = format!("'{}' not found", path);
-				return Err(io::Error::with_message(io::ErrorKind::NotFound, message));
+				Err(io::Error::with_message(io::ErrorKind::NotFound, message))
 			}
 			std::io::ErrorKind::Other => {
 				let message = format!("failed to create '{}'", path);
-				return Err(io::Error::with_message(io::ErrorKind::Other, message));
+				Err(io::Error::with_message(io::ErrorKind::Other, message))
 			}
 			std::io::ErrorKind::Interrupted => {
 				let message = format!("interrupted while creating '{}'", path);
-				return Err(io::Error::with_message(io::ErrorKind::Interrupted, message));
+				Err(io::Error::with_message(io::ErrorKind::Interrupted, message))
 			}
-			_ => return Err(io::Error::from(error)),
+			_ => Err(io::Error::from(error)),
 		},
 	}
 }
@@ -1,5 +1,6 @@
 mod cursor;
 mod file;
+mod prefixed;
 mod reader;
 mod seek;
 pub mod stdio;
@@ -7,6 +8,7 @@ mod writer;
 
 pub use cursor::Cursor;
 pub use file::File;
+pub use prefixed::PrefixedReader;
 pub use reader::{
 	BufferedReader, BufferedWriter, DEFAULT_BUFFER_SIZE, MediaRead, ReadPrimitives, StdReadAdapter,
 };
@@ -0,0 +1,53 @@
+// replays bytes that were already read, probing a pipe, before reading on from `inner`
+pub struct PrefixedReader<R> {
+	prefix: Vec<u8>,
+	pos: usize,
+	inner: R,
+}
+
+impl<R> PrefixedReader<R> {
+	#[inline]
+	pub const fn new(prefix: Vec<u8>, inner: R) -> Self {
+		Self { prefix, pos: 0, inner }
+	}
+
+	#[inline]
+	pub fn into_inner(self) -> R {
+		self.inner
+	}
+}
+
+impl<R: crate::io::MediaRead> crate::io::MediaRead for PrefixedReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
+		if self.pos >= self.prefix.len() {
+			return self.inner.read(buf);
+		}
+		let remaining = &self.prefix[self.pos..];
+		let amt = core::cmp::min(remaining.len(), buf.len());
+		buf[..amt].copy_from_slice(&remaining[..amt]);
+		self.pos += amt;
+		Ok(amt)
+	}
+}
+
+// the replayed bytes are gone from `inner`, positions in it no longer match the stream
+impl<R> crate::io::MediaSeek for PrefixedReader<R> {
+	fn seek(&mut self, _pos: crate::io::SeekFrom) -> crate::io::Result<u64> {
+		Err(crate::io::Error::not_seekable())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::io::{Cursor, ReadPrimitives};
+
+	#[test]
+	fn test_prefix_is_read_first() {
+		let mut reader = PrefixedReader::new(vec![1, 2, 3], Cursor::new(vec![4, 5]));
+		let mut buf = [0u8; 5];
+		reader.read_exact(&mut buf).unwrap();
+		assert_eq!(buf, [1, 2, 3, 4, 5]);
+		assert_eq!(crate::io::MediaRead::read(&mut reader, &mut buf).unwrap(), 0);
+	}
+}
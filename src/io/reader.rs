@@ -18,6 +18,20 @@ pub trait ReadPrimitives: MediaRead {
 		Ok(())
 	}
 
+	// reads until `buf` is full or the input ends, pipes hand out short reads
+	fn read_to_fill(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
+		let mut filled = 0;
+		while filled < buf.len() {
+			match self.read(&mut buf[filled..]) {
+				Ok(0) => break,
+				Ok(n) => filled += n,
+				Err(e) if matches!(e.kind(), crate::io::ErrorKind::Interrupted) => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(filled)
+	}
+
 	#[inline]
 	fn read_u8(&mut self) -> crate::io::Result<u8> {
 		let mut buf = [0u8; 1];
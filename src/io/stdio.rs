@@ -8,12 +8,25 @@ impl StdinAdapter {
 	}
 }
 
+impl Default for StdinAdapter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl crate::io::MediaRead for StdinAdapter {
 	fn read(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
 		std::io::stdin().read(buf).map_err(crate::io::Error::from)
 	}
 }
 
+// pipes cannot seek, demuxers and muxers that need to get an error instead
+impl crate::io::MediaSeek for StdinAdapter {
+	fn seek(&mut self, _pos: crate::io::SeekFrom) -> crate::io::Result<u64> {
+		Err(crate::io::Error::not_seekable())
+	}
+}
+
 pub struct StdoutAdapter;
 
 impl StdoutAdapter {
@@ -22,6 +35,12 @@ impl StdoutAdapter {
 	}
 }
 
+impl Default for StdoutAdapter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl crate::io::MediaWrite for StdoutAdapter {
 	fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
 		std::io::stdout().write(buf).map_err(crate::io::Error::from)
@@ -32,46 +51,86 @@ impl crate::io::MediaWrite for StdoutAdapter {
 	}
 }
 
+impl crate::io::MediaSeek for StdoutAdapter {
+	fn seek(&mut self, _pos: crate::io::SeekFrom) -> crate::io::Result<u64> {
+		Err(crate::io::Error::not_seekable())
+	}
+}
+
+// "-" stands for stdin
 pub enum StdioSource {
-	Stdin(StdinAdapter),
-	File(std::fs::File),
+	Stdin(crate::io::PrefixedReader<StdinAdapter>),
+	File(crate::io::File),
+}
+
+impl StdioSource {
+	pub fn open(path: &str) -> crate::io::Result<Self> {
+		match path {
+			"-" => Ok(Self::stdin(Vec::new())),
+			path => crate::io::File::open(path).map(StdioSource::File),
+		}
+	}
+
+	// `prefix` holds what was already taken from stdin, it is read again first
+	pub fn stdin(prefix: Vec<u8>) -> Self {
+		StdioSource::Stdin(crate::io::PrefixedReader::new(prefix, StdinAdapter::new()))
+	}
 }
 
 impl crate::io::MediaRead for StdioSource {
 	fn read(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
 		match self {
 			StdioSource::Stdin(stdin) => stdin.read(buf),
-			StdioSource::File(file) => {
-				use std::io::Read;
-				file.read(buf).map_err(crate::io::Error::from)
-			}
+			StdioSource::File(file) => crate::io::MediaRead::read(file, buf),
+		}
+	}
+}
+
+impl crate::io::MediaSeek for StdioSource {
+	fn seek(&mut self, pos: crate::io::SeekFrom) -> crate::io::Result<u64> {
+		match self {
+			StdioSource::Stdin(stdin) => stdin.seek(pos),
+			StdioSource::File(file) => file.seek(pos),
 		}
 	}
 }
 
+// "-" stands for stdout
 pub enum StdioSink {
 	Stdout(StdoutAdapter),
-	File(std::fs::File),
+	File(crate::io::File),
+}
+
+impl StdioSink {
+	pub fn create(path: &str) -> crate::io::Result<Self> {
+		match path {
+			"-" => Ok(StdioSink::Stdout(StdoutAdapter::new())),
+			path => crate::io::File::create(path).map(StdioSink::File),
+		}
+	}
 }
 
 impl crate::io::MediaWrite for StdioSink {
 	fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
 		match self {
 			StdioSink::Stdout(stdout) => stdout.write(buf),
-			StdioSink::File(file) => {
-				use std::io::Write;
-				file.write(buf).map_err(crate::io::Error::from)
-			}
+			StdioSink::File(file) => crate::io::MediaWrite::write(file, buf),
 		}
 	}
 
 	fn flush(&mut self) -> crate::io::Result<()> {
 		match self {
 			StdioSink::Stdout(stdout) => stdout.flush(),
-			StdioSink::File(file) => {
-				use std::io::Write;
-				file.flush().map_err(crate::io::Error::from)
-			}
+			StdioSink::File(file) => crate::io::MediaWrite::flush(file),
+		}
+	}
+}
+
+impl crate::io::MediaSeek for StdioSink {
+	fn seek(&mut self, pos: crate::io::SeekFrom) -> crate::io::Result<u64> {
+		match self {
+			StdioSink::Stdout(stdout) => stdout.seek(pos),
+			StdioSink::File(file) => file.seek(pos),
 		}
 	}
 }
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use crate::core::Transform;
+use crate::core::frame::Frame;
+use crate::io::Result;
+
+// transforms run in order, each one feeding every frame it emits to the next
+#[derive(Default)]
+pub struct TransformChain {
+	transforms: Vec<Box<dyn Transform>>,
+	flushing: usize,
+}
+
+impl TransformChain {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn push(&mut self, transform: Box<dyn Transform>) {
+		self.transforms.push(transform);
+	}
+
+	pub fn with(mut self, transform: Box<dyn Transform>) -> Self {
+		self.push(transform);
+		self
+	}
+
+	pub fn len(&self) -> usize {
+		self.transforms.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.transforms.is_empty()
+	}
+
+	pub fn names(&self) -> Vec<&'static str> {
+		self.transforms.iter().map(|transform| transform.name()).collect()
+	}
+
+	fn run_from(&mut self, from: usize, frames: Vec<Frame>) -> Result<Vec<Frame>> {
+		let mut frames = frames;
+		for transform in self.transforms[from..].iter_mut() {
+			let mut next = Vec::with_capacity(frames.len());
+			for frame in frames {
+				next.extend(transform.apply(frame)?);
+			}
+			frames = next;
+		}
+		Ok(frames)
+	}
+}
+
+impl Transform for TransformChain {
+	fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>> {
+		self.run_from(0, vec![frame])
+	}
+
+	fn name(&self) -> &'static str {
+		"chain"
+	}
+
+	fn latency(&self) -> Duration {
+		self.transforms.iter().map(|transform| transform.latency()).sum()
+	}
+
+	// drains each transform in order, its frames still pass through the ones after it
+	fn flush(&mut self) -> Result<Vec<Frame>> {
+		while self.flushing < self.transforms.len() {
+			let frames = self.transforms[self.flushing].flush()?;
+			if frames.is_empty() {
+				self.flushing += 1;
+				continue;
+			}
+
+			let output = self.run_from(self.flushing + 1, frames)?;
+			if !output.is_empty() {
+				return Ok(output);
+			}
+		}
+		Ok(Vec::new())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+	use crate::core::time::Time;
+
+	// holds every frame back and releases them one by one at the end
+	struct Delay(Vec<Frame>);
+
+	impl Transform for Delay {
+		fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>> {
+			self.0.push(frame);
+			Ok(Vec::new())
+		}
+
+		fn name(&self) -> &'static str {
+			"delay"
+		}
+
+		fn flush(&mut self) -> Result<Vec<Frame>> {
+			Ok(self.0.pop().into_iter().collect())
+		}
+	}
+
+	// splits every frame in two
+	struct Split;
+
+	impl Transform for Split {
+		fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>> {
+			Ok(vec![frame.clone(), frame.with_pts(-1)])
+		}
+
+		fn name(&self) -> &'static str {
+			"split"
+		}
+	}
+
+	#[test]
+	fn test_chain_fans_out_and_drains_in_order() {
+		let mut chain = TransformChain::new().with(Box::new(Delay(Vec::new()))).with(Box::new(Split));
+		for pts in 0..3 {
+			let audio = FrameAudio::new(vec![0; 4], 8000, 1, AudioFormat::PCM16);
+			let frame = Frame::new_audio(audio, Time::new(1, 8000), 0, 0).with_pts(pts);
+			assert!(chain.apply(frame).unwrap().is_empty());
+		}
+
+		let mut drained = Vec::new();
+		loop {
+			let frames = chain.flush().unwrap();
+			if frames.is_empty() {
+				break;
+			}
+			drained.extend(frames.iter().map(|frame| frame.pts));
+		}
+		assert_eq!(drained, vec![2, -1, 1, -1, 0, -1]);
+		assert_eq!(chain.names(), vec!["delay", "split"]);
+	}
+}
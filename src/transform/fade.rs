@@ -0,0 +1,208 @@
+use std::f64::consts::PI;
+
+use crate::core::Transform;
+use crate::core::frame::Frame;
+use crate::core::time::Timestamp;
+use crate::io::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeCurve {
+	#[default]
+	Linear,
+	Logarithmic,
+	Exponential,
+	EqualPower,
+	SCurve,
+}
+
+impl FadeCurve {
+	pub fn parse(value: &str) -> Result<Self> {
+		match value.to_lowercase().as_str() {
+			"linear" | "lin" => Ok(FadeCurve::Linear),
+			"logarithmic" | "log" => Ok(FadeCurve::Logarithmic),
+			"exponential" | "exp" => Ok(FadeCurve::Exponential),
+			"equal-power" | "equal_power" | "qsin" => Ok(FadeCurve::EqualPower),
+			"s-curve" | "scurve" | "s" => Ok(FadeCurve::SCurve),
+			_ => Err(Error::invalid_data(format!("invalid fade curve '{}'", value))),
+		}
+	}
+
+	// gain for a fade-in at `x` in [0, 1], fade-outs use the mirrored position
+	pub fn gain(&self, x: f64) -> f64 {
+		let x = x.clamp(0.0, 1.0);
+		match self {
+			FadeCurve::Linear => x,
+			FadeCurve::Logarithmic => (1.0 + 9.0 * x).log10(),
+			FadeCurve::Exponential => ((4.6 * x).exp() - 1.0) / (4.6f64.exp() - 1.0),
+			FadeCurve::EqualPower => (x * PI / 2.0).sin(),
+			FadeCurve::SCurve => (1.0 - (x * PI).cos()) / 2.0,
+		}
+	}
+}
+
+pub struct Fade {
+	fade_in: Option<f64>,
+	fade_out: Option<f64>,
+	curve: FadeCurve,
+	total: Option<f64>,
+}
+
+impl Fade {
+	pub fn new(fade_in: Option<f64>, fade_out: Option<f64>) -> Self {
+		Self { fade_in, fade_out, curve: FadeCurve::default(), total: None }
+	}
+
+	// "in=2s,out=3s,curve=qsin"; durations take any time syntax except sample counts
+	pub fn parse(value: &str) -> Result<Self> {
+		let mut fade = Self::new(None, None);
+
+		for part in value.split(',') {
+			let invalid = || Error::invalid_data(format!("invalid fade option '{}'", part));
+			let (key, value) = part.split_once('=').ok_or_else(invalid)?;
+			match key.trim() {
+				"in" => fade.fade_in = Some(parse_seconds(value)?),
+				"out" => fade.fade_out = Some(parse_seconds(value)?),
+				"curve" => fade.curve = FadeCurve::parse(value.trim())?,
+				_ => return Err(invalid()),
+			}
+		}
+
+		if fade.fade_in.is_none() && fade.fade_out.is_none() {
+			return Err(Error::invalid_data(format!("fade '{}' has neither in nor out", value)));
+		}
+		Ok(fade)
+	}
+
+	pub fn with_curve(mut self, curve: FadeCurve) -> Self {
+		self.curve = curve;
+		self
+	}
+
+	// length of the stream reaching this transform, needed to place the fade-out
+	pub fn with_total_duration(mut self, seconds: f64) -> Self {
+		self.total = Some(seconds);
+		self
+	}
+
+	pub fn needs_duration(&self) -> bool {
+		self.fade_out.is_some()
+	}
+
+	// `step` is the time between two samples, the last one of a fade-out is silent
+	fn gain_at(&self, time: f64, step: f64) -> f64 {
+		let mut gain = 1.0;
+		if let Some(length) = self.fade_in
+			&& time < length
+		{
+			gain *= self.curve.gain(time / length);
+		}
+		if let (Some(length), Some(total)) = (self.fade_out, self.total)
+			&& time > total - length
+		{
+			let span = length - step;
+			let position = if span > 0.0 { (total - step - time) / span } else { 0.0 };
+			gain *= self.curve.gain(position);
+		}
+		gain
+	}
+}
+
+fn parse_seconds(value: &str) -> Result<f64> {
+	match Timestamp::parse(value)? {
+		Timestamp::Seconds(seconds) if seconds > 0.0 => Ok(seconds),
+		_ => Err(Error::invalid_data(format!("invalid fade duration '{}'", value))),
+	}
+}
+
+impl Transform for Fade {
+	fn apply(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		if self.needs_duration() && self.total.is_none() {
+			return Err(Error::invalid_data("fade out needs the stream duration"));
+		}
+
+		let start = frame.time.to_seconds(frame.pts);
+		let Some(audio) = frame.audio_mut() else {
+			return Ok(vec![frame]);
+		};
+
+		let last = start + audio.nb_samples as f64 / audio.sample_rate as f64;
+		let in_fade = self.fade_in.is_some_and(|length| start < length);
+		let out_fade = match (self.fade_out, self.total) {
+			(Some(length), Some(total)) => last > total - length,
+			_ => false,
+		};
+		if !in_fade && !out_fade {
+			return Ok(vec![frame]);
+		}
+
+		let channels = std::cmp::max(1, audio.channels as usize);
+		let sample_rate = audio.sample_rate as f64;
+		let mut samples = audio.to_f32()?;
+		for (index, chunk) in samples.chunks_mut(channels).enumerate() {
+			let gain = self.gain_at(start + index as f64 / sample_rate, 1.0 / sample_rate) as f32;
+			for sample in chunk.iter_mut() {
+				*sample *= gain;
+			}
+		}
+		audio.write_f32(&samples)?;
+		Ok(vec![frame])
+	}
+
+	fn name(&self) -> &'static str {
+		"fade"
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+	use crate::core::time::Time;
+
+	#[test]
+	fn test_curves_run_from_silence_to_unity() {
+		for curve in ["linear", "log", "exp", "qsin", "scurve"] {
+			let curve = FadeCurve::parse(curve).unwrap();
+			assert!(curve.gain(0.0).abs() < 1e-9);
+			assert!((curve.gain(1.0) - 1.0).abs() < 1e-9);
+			assert!(curve.gain(0.25) < curve.gain(0.75));
+		}
+		assert!(FadeCurve::Logarithmic.gain(0.5) > 0.5);
+		assert!(FadeCurve::Exponential.gain(0.5) < 0.5);
+		assert!((FadeCurve::EqualPower.gain(0.5) - 0.5f64.sqrt()).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_fade_in_and_out_over_frame() {
+		let audio = FrameAudio::new(vec![0; 40], 10, 1, AudioFormat::PCM32);
+		let mut frame = Frame::new_audio(audio, Time::new(1, 10), 0, 0);
+		frame.audio_mut().unwrap().write_f32(&[1.0; 10]).unwrap();
+
+		let mut fade = Fade::parse("in=0.5s,out=200ms").unwrap().with_total_duration(1.0);
+		let samples = fade.apply(frame).unwrap().remove(0).audio().unwrap().to_f32().unwrap();
+		let expected = [0.0, 0.2, 0.4, 0.6, 0.8, 1.0, 1.0, 1.0, 1.0, 0.0];
+		for (sample, expected) in samples.iter().zip(expected) {
+			assert!((sample - expected).abs() < 1e-6, "{:?}", samples);
+		}
+
+		// a longer fade-out steps down evenly to silence on the last sample
+		let audio = FrameAudio::new(vec![0; 40], 10, 1, AudioFormat::PCM32);
+		let mut frame = Frame::new_audio(audio, Time::new(1, 10), 0, 0);
+		frame.audio_mut().unwrap().write_f32(&[1.0; 10]).unwrap();
+		let mut fade = Fade::parse("out=0.5s").unwrap().with_total_duration(1.0);
+		let samples = fade.apply(frame).unwrap().remove(0).audio().unwrap().to_f32().unwrap();
+		let expected = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.75, 0.5, 0.25, 0.0];
+		for (sample, expected) in samples.iter().zip(expected) {
+			assert!((sample - expected).abs() < 1e-6, "{:?}", samples);
+		}
+		assert_eq!(samples[9], 0.0);
+	}
+
+	#[test]
+	fn test_fade_out_requires_duration() {
+		let audio = FrameAudio::new(vec![0; 4], 10, 1, AudioFormat::PCM16);
+		let frame = Frame::new_audio(audio, Time::new(1, 10), 0, 0);
+		assert!(Fade::parse("out=1s").unwrap().apply(frame).is_err());
+		assert!(Fade::parse("curve=log").is_err());
+	}
+}
@@ -0,0 +1,262 @@
+// ITU-R BS.1770 loudness and true-peak measurement.
+
+use crate::core::frame::{ChannelLayout, Speaker};
+
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+const BLOCK_STEPS: usize = 4; // 400ms gating block made of 100ms steps
+const OVERSAMPLE: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+	b0: f64,
+	b1: f64,
+	b2: f64,
+	a1: f64,
+	a2: f64,
+	z1: f64,
+	z2: f64,
+}
+
+impl Biquad {
+	fn process(&mut self, input: f64) -> f64 {
+		let output = self.b0 * input + self.z1;
+		self.z1 = self.b1 * input - self.a1 * output + self.z2;
+		self.z2 = self.b2 * input - self.a2 * output;
+		output
+	}
+
+	// pre-filter: high shelf modelling the acoustic effect of the head
+	fn shelf(sample_rate: u32) -> Self {
+		let f0 = 1681.974450955533;
+		let gain = 3.999843853973347;
+		let q = 0.7071752369554196;
+
+		let k = (std::f64::consts::PI * f0 / sample_rate as f64).tan();
+		let vh = 10f64.powf(gain / 20.0);
+		let vb = vh.powf(0.4996667741545416);
+		let a0 = 1.0 + k / q + k * k;
+
+		Self {
+			b0: (vh + vb * k / q + k * k) / a0,
+			b1: 2.0 * (k * k - vh) / a0,
+			b2: (vh - vb * k / q + k * k) / a0,
+			a1: 2.0 * (k * k - 1.0) / a0,
+			a2: (1.0 - k / q + k * k) / a0,
+			..Default::default()
+		}
+	}
+
+	// rlb weighting: second order high pass
+	fn high_pass(sample_rate: u32) -> Self {
+		let f0 = 38.13547087602444;
+		let q = 0.5003270373238773;
+
+		let k = (std::f64::consts::PI * f0 / sample_rate as f64).tan();
+		let a0 = 1.0 + k / q + k * k;
+
+		Self {
+			b0: 1.0,
+			b1: -2.0,
+			b2: 1.0,
+			a1: 2.0 * (k * k - 1.0) / a0,
+			a2: (1.0 - k / q + k * k) / a0,
+			..Default::default()
+		}
+	}
+}
+
+pub struct LoudnessMeter {
+	channels: usize,
+	weights: Vec<f64>,
+	filters: Vec<(Biquad, Biquad)>,
+	step_size: usize,
+	step_position: usize,
+	step_energy: f64,
+	steps: Vec<f64>,
+	blocks: Vec<f64>,
+	peak: f64,
+	true_peak: f64,
+	interpolator: Vec<f64>,
+	history: Vec<Vec<f64>>,
+}
+
+impl LoudnessMeter {
+	pub fn new(sample_rate: u32, channels: u8) -> Self {
+		let channels = std::cmp::max(1, channels as usize);
+		let filter = (Biquad::shelf(sample_rate), Biquad::high_pass(sample_rate));
+
+		Self {
+			channels,
+			weights: channel_weights(ChannelLayout::default_for(channels as u8)),
+			filters: vec![filter; channels],
+			step_size: std::cmp::max(1, sample_rate as usize / 10),
+			step_position: 0,
+			step_energy: 0.0,
+			steps: Vec::new(),
+			blocks: Vec::new(),
+			peak: 0.0,
+			true_peak: 0.0,
+			interpolator: interpolation_filter(),
+			history: vec![vec![0.0; TAPS_PER_PHASE]; channels],
+		}
+	}
+
+	pub fn with_layout(mut self, layout: ChannelLayout) -> Self {
+		if layout.channels() as usize == self.channels {
+			self.weights = channel_weights(layout);
+		}
+		self
+	}
+
+	// interleaved samples in [-1.0, 1.0]
+	pub fn add_samples(&mut self, samples: &[f32]) {
+		for frame in samples.chunks_exact(self.channels) {
+			let mut energy = 0.0;
+			for (channel, &sample) in frame.iter().enumerate() {
+				let sample = sample as f64;
+				self.peak = self.peak.max(sample.abs());
+				self.update_true_peak(channel, sample);
+
+				let (shelf, high_pass) = &mut self.filters[channel];
+				let weighted = high_pass.process(shelf.process(sample));
+				energy += self.weights[channel] * weighted * weighted;
+			}
+
+			self.step_energy += energy;
+			self.step_position += 1;
+			if self.step_position == self.step_size {
+				self.push_step();
+			}
+		}
+	}
+
+	fn push_step(&mut self) {
+		self.steps.push(self.step_energy / self.step_size as f64);
+		self.step_energy = 0.0;
+		self.step_position = 0;
+
+		if self.steps.len() >= BLOCK_STEPS {
+			let block = &self.steps[self.steps.len() - BLOCK_STEPS..];
+			self.blocks.push(block.iter().sum::<f64>() / BLOCK_STEPS as f64);
+		}
+	}
+
+	fn update_true_peak(&mut self, channel: usize, sample: f64) {
+		let history = &mut self.history[channel];
+		history.rotate_right(1);
+		history[0] = sample;
+
+		for phase in 0..OVERSAMPLE {
+			let mut value = 0.0;
+			for (tap, &past) in history.iter().enumerate() {
+				value += past * self.interpolator[tap * OVERSAMPLE + phase];
+			}
+			self.true_peak = self.true_peak.max(value.abs());
+		}
+	}
+
+	// gated integrated loudness in LUFS, None when everything is below the absolute gate
+	pub fn integrated(&self) -> Option<f64> {
+		let above_absolute: Vec<f64> =
+			self.blocks.iter().copied().filter(|&power| to_lufs(power) > ABSOLUTE_GATE).collect();
+		if above_absolute.is_empty() {
+			return None;
+		}
+
+		let mean = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+		let threshold = to_lufs(mean) + RELATIVE_GATE;
+
+		let gated: Vec<f64> =
+			above_absolute.into_iter().filter(|&power| to_lufs(power) > threshold).collect();
+		if gated.is_empty() {
+			return None;
+		}
+
+		Some(to_lufs(gated.iter().sum::<f64>() / gated.len() as f64))
+	}
+
+	pub fn sample_peak(&self) -> f64 {
+		self.peak
+	}
+
+	pub fn true_peak(&self) -> f64 {
+		self.true_peak.max(self.peak)
+	}
+}
+
+fn to_lufs(power: f64) -> f64 {
+	if power <= 0.0 {
+		return f64::NEG_INFINITY;
+	}
+	-0.691 + 10.0 * power.log10()
+}
+
+// surround channels are boosted and the lfe is not measured
+fn channel_weights(layout: ChannelLayout) -> Vec<f64> {
+	if layout.is_unknown() {
+		return vec![1.0; layout.channels() as usize];
+	}
+	let weight = |speaker: Speaker| match speaker {
+		Speaker::LowFrequency => 0.0,
+		Speaker::BackLeft | Speaker::BackRight | Speaker::SideLeft | Speaker::SideRight => 1.41,
+		_ => 1.0,
+	};
+	layout.speakers().into_iter().map(weight).collect()
+}
+
+// windowed sinc low pass at the original nyquist, split into OVERSAMPLE phases
+fn interpolation_filter() -> Vec<f64> {
+	let length = TAPS_PER_PHASE * OVERSAMPLE;
+	let center = (length - 1) as f64 / 2.0;
+
+	(0..length)
+		.map(|n| {
+			let x = (n as f64 - center) / OVERSAMPLE as f64;
+			let sinc =
+				if x == 0.0 { 1.0 } else { (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x) };
+			let window = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / (length - 1) as f64).cos();
+			sinc * window
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sine(sample_rate: u32, frequency: f64, amplitude: f64, seconds: f64) -> Vec<f32> {
+		let count = (sample_rate as f64 * seconds) as usize;
+		(0..count)
+			.map(|n| {
+				let t = n as f64 / sample_rate as f64;
+				(amplitude * (2.0 * std::f64::consts::PI * frequency * t).sin()) as f32
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_reference_tone_loudness() {
+		// a full scale 1kHz sine on one channel reads -3.01 LUFS
+		let mut meter = LoudnessMeter::new(48000, 1);
+		meter.add_samples(&sine(48000, 1000.0, 1.0, 5.0));
+		let loudness = meter.integrated().unwrap();
+		assert!((loudness + 3.01).abs() < 0.1, "loudness was {}", loudness);
+	}
+
+	#[test]
+	fn test_silence_is_gated() {
+		let mut meter = LoudnessMeter::new(44100, 2);
+		meter.add_samples(&vec![0.0; 44100 * 2]);
+		assert!(meter.integrated().is_none());
+	}
+
+	#[test]
+	fn test_true_peak_is_at_least_sample_peak() {
+		let mut meter = LoudnessMeter::new(44100, 1);
+		meter.add_samples(&sine(44100, 11025.0, 0.5, 0.5));
+		assert!(meter.true_peak() >= meter.sample_peak());
+		assert!(meter.true_peak() < 0.6);
+	}
+}
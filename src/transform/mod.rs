@@ -1,5 +1,21 @@
+pub mod chain;
+pub mod fade;
+pub mod loudness;
 pub mod normalize;
+pub mod remix;
+pub mod resample;
+pub mod reverse;
+pub mod speed;
+pub mod trim;
 pub mod volume;
 
-pub use normalize::Normalize;
-pub use volume::Volume;
+pub use chain::TransformChain;
+pub use fade::{Fade, FadeCurve};
+pub use loudness::LoudnessMeter;
+pub use normalize::{Normalize, NormalizeTarget};
+pub use remix::Remix;
+pub use resample::{Resample, ResampleQuality};
+pub use reverse::Reverse;
+pub use speed::{Speed, SpeedMode};
+pub use trim::{Trim, TrimEnd};
+pub use volume::{ClipMode, Volume};
@@ -1,15 +1,158 @@
+use super::loudness::LoudnessMeter;
+use super::volume::{db_to_linear, linear_to_db};
 use crate::core::Transform;
 use crate::core::frame::Frame;
-use crate::io::Result;
+use crate::io::{Error, Result};
 
-pub struct Normalize {}
+const DEFAULT_PEAK_DBFS: f32 = -1.0;
+const DEFAULT_CEILING_DBTP: f32 = -1.0;
 
-impl Normalize {}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalizeTarget {
+	Peak(f32),
+	Loudness(f32),
+}
+
+impl NormalizeTarget {
+	// "-1dbfs", "0.9" (linear peak), "-16lufs" or "true" for the default peak target
+	pub fn parse(value: &str) -> Result<Self> {
+		let lower = value.trim().to_lowercase();
+		let invalid = || Error::invalid_data(format!("invalid normalize target '{}'", value));
+
+		if lower == "true" {
+			return Ok(NormalizeTarget::Peak(DEFAULT_PEAK_DBFS));
+		}
+
+		if let Some(lufs) = lower.strip_suffix("lufs").or_else(|| lower.strip_suffix("lkfs")) {
+			let lufs = lufs.trim().parse::<f32>().map_err(|_| invalid())?;
+			return Ok(NormalizeTarget::Loudness(lufs));
+		}
+
+		if let Some(db) = lower.strip_suffix("dbfs").or_else(|| lower.strip_suffix("db")) {
+			let db = db.trim().parse::<f32>().map_err(|_| invalid())?;
+			if db > 0.0 {
+				return Err(invalid());
+			}
+			return Ok(NormalizeTarget::Peak(db));
+		}
+
+		let linear = lower.parse::<f32>().map_err(|_| invalid())?;
+		if linear <= 0.0 || linear > 1.0 {
+			return Err(invalid());
+		}
+		Ok(NormalizeTarget::Peak(linear_to_db(linear)))
+	}
+}
+
+pub struct Normalize {
+	target: NormalizeTarget,
+	ceiling: f32,
+	meter: Option<LoudnessMeter>,
+	gain: Option<f32>,
+}
+
+impl Normalize {
+	pub fn new(target: NormalizeTarget) -> Self {
+		Self { target, ceiling: DEFAULT_CEILING_DBTP, meter: None, gain: None }
+	}
+
+	// "-16lufs" or "-16lufs,tp=-2" to change the true-peak ceiling
+	pub fn parse(value: &str) -> Result<Self> {
+		let mut parts = value.split(',');
+		let target = NormalizeTarget::parse(parts.next().unwrap_or_default())?;
+		let mut normalize = Self::new(target);
+
+		for part in parts {
+			match part.split_once('=') {
+				Some(("tp", ceiling)) => {
+					let ceiling = ceiling.trim().to_lowercase();
+					let ceiling = ceiling.strip_suffix("dbtp").unwrap_or(&ceiling);
+					let ceiling = ceiling
+						.parse::<f32>()
+						.map_err(|_| Error::invalid_data(format!("invalid true peak ceiling '{}'", part)))?;
+					normalize = normalize.with_ceiling(ceiling);
+				}
+				_ => return Err(Error::invalid_data(format!("unknown normalize option '{}'", part))),
+			}
+		}
+
+		Ok(normalize)
+	}
+
+	pub fn with_ceiling(mut self, ceiling: f32) -> Self {
+		self.ceiling = ceiling;
+		self
+	}
+
+	pub fn target(&self) -> NormalizeTarget {
+		self.target
+	}
+
+	// first pass: feed every frame of the stream before calling `finish_analysis`
+	pub fn analyze(&mut self, frame: &Frame) -> Result<()> {
+		let Some(audio) = frame.audio() else {
+			return Ok(());
+		};
+
+		let samples = audio.to_f32()?;
+		let meter = self.meter.get_or_insert_with(|| {
+			LoudnessMeter::new(audio.sample_rate, audio.channels).with_layout(audio.layout)
+		});
+		meter.add_samples(&samples);
+		Ok(())
+	}
+
+	pub fn finish_analysis(&mut self) -> f32 {
+		let gain = match &self.meter {
+			Some(meter) => self.compute_gain(meter),
+			None => 1.0,
+		};
+		self.gain = Some(gain);
+		gain
+	}
+
+	pub fn gain(&self) -> Option<f32> {
+		self.gain
+	}
+
+	fn compute_gain(&self, meter: &LoudnessMeter) -> f32 {
+		match self.target {
+			NormalizeTarget::Peak(target) => {
+				let peak = meter.sample_peak() as f32;
+				if peak <= 0.0 {
+					return 1.0;
+				}
+				db_to_linear(target) / peak
+			}
+			NormalizeTarget::Loudness(target) => {
+				let Some(integrated) = meter.integrated() else {
+					return 1.0;
+				};
+				let mut gain_db = target - integrated as f32;
+
+				let true_peak = meter.true_peak() as f32;
+				if true_peak > 0.0 {
+					let headroom = self.ceiling - linear_to_db(true_peak);
+					gain_db = gain_db.min(headroom);
+				}
+				db_to_linear(gain_db)
+			}
+		}
+	}
+}
 
 impl Transform for Normalize {
-	fn apply(&mut self, mut frame: Frame) -> Result<Frame> {
-		if let Some(_audio_frame) = frame.audio_mut() {}
-		Ok(frame)
+	fn apply(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		let gain = self.gain.ok_or_else(|| Error::invalid_data("normalize needs an analysis pass"))?;
+
+		if let Some(audio) = frame.audio_mut() {
+			let mut samples = audio.to_f32()?;
+			for sample in samples.iter_mut() {
+				*sample = (*sample * gain).clamp(-1.0, 1.0);
+			}
+			audio.write_f32(&samples)?;
+		}
+		Ok(vec![frame])
 	}
 
 	fn name(&self) -> &'static str {
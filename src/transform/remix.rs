@@ -0,0 +1,241 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use crate::core::Transform;
+use crate::core::frame::{ChannelLayout, Frame, Speaker};
+use crate::io::{Error, Result};
+
+// rows are output channels, columns are input channels
+pub type Matrix = Vec<Vec<f32>>;
+
+// where a speaker missing from the target layout is folded, first option that fits wins
+fn fold_options(speaker: Speaker) -> &'static [&'static [(Speaker, f32)]] {
+	use Speaker::*;
+	const C: f32 = FRAC_1_SQRT_2;
+	match speaker {
+		FrontCenter => &[&[(FrontLeft, C), (FrontRight, C)]],
+		BackLeft => &[&[(SideLeft, 1.0)], &[(FrontLeft, C)]],
+		BackRight => &[&[(SideRight, 1.0)], &[(FrontRight, C)]],
+		SideLeft => &[&[(BackLeft, 1.0)], &[(FrontLeft, C)]],
+		SideRight => &[&[(BackRight, 1.0)], &[(FrontRight, C)]],
+		BackCenter => &[
+			&[(BackLeft, C), (BackRight, C)],
+			&[(SideLeft, C), (SideRight, C)],
+			&[(FrontLeft, C), (FrontRight, C)],
+		],
+		FrontLeftOfCenter => &[&[(FrontLeft, 1.0)]],
+		FrontRightOfCenter => &[&[(FrontRight, 1.0)]],
+		TopFrontLeft => &[&[(FrontLeft, C)]],
+		TopFrontRight => &[&[(FrontRight, C)]],
+		TopCenter | TopFrontCenter => &[&[(FrontCenter, C)], &[(FrontLeft, 0.5), (FrontRight, 0.5)]],
+		TopBackLeft => &[&[(BackLeft, C)], &[(SideLeft, C)], &[(FrontLeft, C)]],
+		TopBackRight => &[&[(BackRight, C)], &[(SideRight, C)], &[(FrontRight, C)]],
+		TopBackCenter => &[&[(BackCenter, C)], &[(BackLeft, 0.5), (BackRight, 0.5)]],
+		// the lfe is dropped on downmix, as in ITU-R BS.775
+		FrontLeft | FrontRight | LowFrequency => &[],
+	}
+}
+
+// standard coefficients between two layouts (ITU-R BS.775 for 5.1 and 7.1 to stereo)
+pub fn downmix_matrix(from: ChannelLayout, to: ChannelLayout) -> Matrix {
+	let inputs = from.channels() as usize;
+	let outputs = to.channels() as usize;
+
+	if from == to || from.is_unknown() || to.is_unknown() {
+		return identity(outputs, inputs);
+	}
+
+	if to == ChannelLayout::MONO {
+		if from == ChannelLayout::MONO {
+			return identity(1, 1);
+		}
+		let stereo = downmix_matrix(from, ChannelLayout::STEREO);
+		let row = (0..inputs).map(|i| 0.5 * (stereo[0][i] + stereo[1][i])).collect();
+		return vec![row];
+	}
+
+	let mut matrix = vec![vec![0.0; inputs]; outputs];
+
+	if from == ChannelLayout::MONO {
+		let targets: &[Speaker] = if to.contains(Speaker::FrontCenter) {
+			&[Speaker::FrontCenter]
+		} else {
+			&[Speaker::FrontLeft, Speaker::FrontRight]
+		};
+		for &speaker in targets {
+			if let Some(row) = to.index_of(speaker) {
+				matrix[row][0] = 1.0;
+			}
+		}
+		return matrix;
+	}
+
+	for (column, speaker) in from.speakers().into_iter().enumerate() {
+		if let Some(row) = to.index_of(speaker) {
+			matrix[row][column] += 1.0;
+			continue;
+		}
+
+		let option = fold_options(speaker)
+			.iter()
+			.find(|targets| targets.iter().all(|(target, _)| to.contains(*target)));
+		for (target, gain) in option.map(|o| o.iter()).into_iter().flatten() {
+			if let Some(row) = to.index_of(*target) {
+				matrix[row][column] += gain;
+			}
+		}
+	}
+
+	matrix
+}
+
+fn identity(outputs: usize, inputs: usize) -> Matrix {
+	(0..outputs)
+		.map(|row| (0..inputs).map(|col| if row == col { 1.0 } else { 0.0 }).collect())
+		.collect()
+}
+
+// "1,0,0.707|0,1,0.707": rows split by '|', coefficients by ','
+pub fn parse_matrix(value: &str) -> Result<Matrix> {
+	let invalid = || Error::invalid_data(format!("invalid mix matrix '{}'", value));
+	let matrix = value
+		.split('|')
+		.map(|row| row.split(',').map(|c| c.trim().parse::<f32>().map_err(|_| invalid())).collect())
+		.collect::<Result<Matrix>>()?;
+
+	let columns = matrix.first().map(|row| row.len()).unwrap_or(0);
+	if columns == 0 || matrix.iter().any(|row| row.len() != columns) {
+		return Err(invalid());
+	}
+	Ok(matrix)
+}
+
+pub struct Remix {
+	target: ChannelLayout,
+	custom: Option<Matrix>,
+	resolved: Option<(ChannelLayout, Matrix)>,
+}
+
+impl Remix {
+	pub fn new(target: ChannelLayout) -> Self {
+		Self { target, custom: None, resolved: None }
+	}
+
+	pub fn with_matrix(mut self, matrix: Matrix) -> Self {
+		self.custom = Some(matrix);
+		self
+	}
+
+	pub fn target(&self) -> ChannelLayout {
+		self.target
+	}
+
+	fn matrix_for(&mut self, source: ChannelLayout) -> Result<&Matrix> {
+		let stale = self.resolved.as_ref().map(|(layout, _)| *layout != source).unwrap_or(true);
+		if stale {
+			let matrix = match &self.custom {
+				Some(custom) => {
+					let outputs = self.target.channels() as usize;
+					if custom.len() != outputs || custom[0].len() != source.channels() as usize {
+						let message = format!(
+							"mix matrix must be {}x{} for {} to {}",
+							outputs,
+							source.channels(),
+							source.name(),
+							self.target.name()
+						);
+						return Err(Error::invalid_data(message));
+					}
+					custom.clone()
+				}
+				None => downmix_matrix(source, self.target),
+			};
+			self.resolved = Some((source, matrix));
+		}
+		Ok(&self.resolved.as_ref().unwrap().1)
+	}
+}
+
+impl Transform for Remix {
+	fn apply(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		let target = self.target;
+		let Some(audio) = frame.audio_mut() else {
+			return Ok(vec![frame]);
+		};
+
+		if audio.layout == target && self.custom.is_none() {
+			return Ok(vec![frame]);
+		}
+
+		let inputs = audio.channels as usize;
+		let samples = audio.to_f32()?;
+		let matrix = self.matrix_for(audio.layout)?;
+
+		let mut output = Vec::with_capacity(samples.len() / inputs * matrix.len());
+		for frame_samples in samples.chunks_exact(inputs) {
+			for row in matrix.iter() {
+				let value: f32 = row.iter().zip(frame_samples).map(|(gain, sample)| gain * sample).sum();
+				output.push(value);
+			}
+		}
+
+		audio.channels = target.channels();
+		audio.layout = target;
+		audio.write_f32(&output)?;
+		Ok(vec![frame])
+	}
+
+	fn name(&self) -> &'static str {
+		"remix"
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+	use crate::core::time::Time;
+
+	#[test]
+	fn test_surround_to_stereo_coefficients() {
+		// FL FR FC LFE BL BR
+		let matrix = downmix_matrix(ChannelLayout::SURROUND_5_1, ChannelLayout::STEREO);
+		let c = FRAC_1_SQRT_2;
+		assert_eq!(matrix[0], vec![1.0, 0.0, c, 0.0, c, 0.0]);
+		assert_eq!(matrix[1], vec![0.0, 1.0, c, 0.0, 0.0, c]);
+	}
+
+	#[test]
+	fn test_stereo_to_mono_and_back() {
+		assert_eq!(downmix_matrix(ChannelLayout::STEREO, ChannelLayout::MONO), vec![vec![0.5, 0.5]]);
+		assert_eq!(
+			downmix_matrix(ChannelLayout::MONO, ChannelLayout::STEREO),
+			vec![vec![1.0], vec![1.0]]
+		);
+	}
+
+	#[test]
+	fn test_remix_frame_to_stereo() {
+		let samples: Vec<f32> = vec![0.1, 0.2, 0.3, 0.4, 0.0, 0.0];
+		let mut audio = FrameAudio::new(vec![0; 24], 48000, 6, AudioFormat::PCM32);
+		audio.write_f32(&samples).unwrap();
+		let frame = Frame::new_audio(audio, Time::new(1, 48000), 0, 0);
+
+		let frame = Remix::new(ChannelLayout::STEREO).apply(frame).unwrap().remove(0);
+		let audio = frame.audio().unwrap();
+		assert_eq!(audio.channels, 2);
+		assert_eq!(audio.nb_samples, 1);
+
+		let output = audio.to_f32().unwrap();
+		assert!((output[0] - (0.1 + 0.3 * FRAC_1_SQRT_2)).abs() < 1e-6);
+		assert!((output[1] - (0.2 + 0.3 * FRAC_1_SQRT_2)).abs() < 1e-6);
+	}
+
+	#[test]
+	fn test_custom_matrix_shape_is_checked() {
+		let matrix = parse_matrix("1,0|0,1|0.5,0.5").unwrap();
+		let audio = FrameAudio::new(vec![0; 4], 48000, 2, AudioFormat::PCM16);
+		let frame = Frame::new_audio(audio, Time::new(1, 48000), 0, 0);
+		assert!(Remix::new(ChannelLayout::STEREO).with_matrix(matrix).apply(frame).is_err());
+		assert!(parse_matrix("1,0|1").is_err());
+	}
+}
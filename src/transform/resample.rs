@@ -0,0 +1,372 @@
+use std::time::Duration;
+
+use crate::core::Transform;
+use crate::core::frame::Frame;
+use crate::core::time::{Time, rescale};
+use crate::io::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleQuality {
+	Fast,
+	Low,
+	#[default]
+	Medium,
+	High,
+}
+
+impl ResampleQuality {
+	pub fn parse(value: &str) -> Result<Self> {
+		match value.to_lowercase().as_str() {
+			"fast" | "linear" => Ok(ResampleQuality::Fast),
+			"low" => Ok(ResampleQuality::Low),
+			"medium" => Ok(ResampleQuality::Medium),
+			"high" | "best" => Ok(ResampleQuality::High),
+			_ => Err(Error::invalid_data(format!("invalid resampler quality '{}'", value))),
+		}
+	}
+
+	// (zero crossings per side, kaiser beta, table resolution per input sample)
+	fn sinc_params(&self) -> Option<(usize, f64, usize)> {
+		match self {
+			ResampleQuality::Fast => None,
+			ResampleQuality::Low => Some((8, 6.0, 64)),
+			ResampleQuality::Medium => Some((16, 8.0, 256)),
+			ResampleQuality::High => Some((32, 10.0, 1024)),
+		}
+	}
+}
+
+// windowed sinc low pass sampled `resolution` times per input sample, one side only
+struct Kernel {
+	table: Vec<f32>,
+	resolution: usize,
+	half_width: usize,
+}
+
+impl Kernel {
+	fn new(from: u32, to: u32, zero_crossings: usize, beta: f64, resolution: usize) -> Self {
+		// leave a small transition band below the lower nyquist
+		let cutoff = 0.97 * (to as f64 / from as f64).min(1.0);
+		let half_width = (zero_crossings as f64 / cutoff).ceil() as usize;
+		let length = half_width * resolution + 2;
+		let norm = bessel_i0(beta);
+
+		let table = (0..length)
+			.map(|i| {
+				let x = i as f64 / resolution as f64;
+				let ratio = x / half_width as f64;
+				if ratio >= 1.0 {
+					return 0.0;
+				}
+				let window = bessel_i0(beta * (1.0 - ratio * ratio).sqrt()) / norm;
+				(cutoff * sinc(cutoff * x) * window) as f32
+			})
+			.collect();
+
+		Self { table, resolution, half_width }
+	}
+
+	fn weight(&self, distance: f64) -> f32 {
+		let position = distance.abs() * self.resolution as f64;
+		let index = position as usize;
+		if index + 1 >= self.table.len() {
+			return 0.0;
+		}
+		let fraction = (position - index as f64) as f32;
+		self.table[index] + (self.table[index + 1] - self.table[index]) * fraction
+	}
+}
+
+pub struct Resample {
+	target_rate: u32,
+	quality: ResampleQuality,
+	source_rate: u32,
+	kernel: Option<Kernel>,
+	channels: usize,
+	buffers: Vec<Vec<f32>>,
+	buffer_start: u64,
+	input_count: u64,
+	output_count: u64,
+	base_pts: i64,
+	template: Option<Frame>,
+}
+
+impl Resample {
+	pub fn new(target_rate: u32) -> Self {
+		Self {
+			target_rate,
+			quality: ResampleQuality::default(),
+			source_rate: 0,
+			kernel: None,
+			channels: 0,
+			buffers: Vec::new(),
+			buffer_start: 0,
+			input_count: 0,
+			output_count: 0,
+			base_pts: 0,
+			template: None,
+		}
+	}
+
+	pub fn with_quality(mut self, quality: ResampleQuality) -> Self {
+		self.quality = quality;
+		self
+	}
+
+	pub fn target_rate(&self) -> u32 {
+		self.target_rate
+	}
+
+	fn init(&mut self, frame: &Frame, sample_rate: u32, channels: u8) {
+		self.source_rate = sample_rate;
+		self.channels = std::cmp::max(1, channels as usize);
+		self.buffers = vec![Vec::new(); self.channels];
+		self.base_pts = rescale(frame.pts, frame.time, Time::new(1, self.target_rate));
+
+		if let Some((zero_crossings, beta, resolution)) = self.quality.sinc_params() {
+			let kernel = Kernel::new(sample_rate, self.target_rate, zero_crossings, beta, resolution);
+			self.kernel = Some(kernel);
+		}
+	}
+
+	fn half_width(&self) -> u64 {
+		self.kernel.as_ref().map(|k| k.half_width as u64).unwrap_or(1)
+	}
+
+	// input position of output sample `n`: integer part and fraction
+	fn position(&self, n: u64) -> (u64, f64) {
+		let scaled = n as u128 * self.source_rate as u128;
+		let whole = scaled / self.target_rate as u128;
+		let rest = scaled % self.target_rate as u128;
+		(whole as u64, rest as f64 / self.target_rate as f64)
+	}
+
+	fn total_output(&self) -> u64 {
+		let scaled = self.input_count as u128 * self.target_rate as u128;
+		scaled.div_ceil(self.source_rate as u128) as u64
+	}
+
+	fn sample(&self, channel: usize, index: i64) -> f32 {
+		if index < self.buffer_start as i64 {
+			return 0.0;
+		}
+		let offset = (index - self.buffer_start as i64) as usize;
+		self.buffers[channel].get(offset).copied().unwrap_or(0.0)
+	}
+
+	fn produce(&mut self, flushing: bool) -> Vec<f32> {
+		let half = self.half_width();
+		let total = self.total_output();
+		let mut output = Vec::new();
+		let mut weights = Vec::new();
+
+		loop {
+			if flushing && self.output_count >= total {
+				break;
+			}
+			let (whole, fraction) = self.position(self.output_count);
+			if !flushing && whole + half >= self.input_count {
+				break;
+			}
+
+			let first = whole as i64 - half as i64 + 1;
+			weights.clear();
+			match &self.kernel {
+				Some(kernel) => {
+					for tap in 0..(2 * half) as i64 {
+						weights.push(kernel.weight(fraction - (tap + first - whole as i64) as f64));
+					}
+				}
+				None => weights.extend_from_slice(&[1.0 - fraction as f32, fraction as f32]),
+			}
+
+			for channel in 0..self.channels {
+				let mut value = 0.0;
+				for (tap, weight) in weights.iter().enumerate() {
+					value += self.sample(channel, first + tap as i64) * weight;
+				}
+				output.push(value);
+			}
+			self.output_count += 1;
+		}
+
+		self.discard_consumed(half);
+		output
+	}
+
+	fn discard_consumed(&mut self, half: u64) {
+		let (whole, _) = self.position(self.output_count);
+		let keep_from = (whole + 1).saturating_sub(half);
+		if keep_from <= self.buffer_start {
+			return;
+		}
+		let drop = (keep_from - self.buffer_start) as usize;
+		for buffer in self.buffers.iter_mut() {
+			buffer.drain(..drop.min(buffer.len()));
+		}
+		self.buffer_start = keep_from;
+	}
+
+	fn emit(&mut self, samples: Vec<f32>) -> Result<Frame> {
+		let mut frame =
+			self.template.clone().ok_or_else(|| Error::invalid_data("resample has no input"))?;
+		let emitted = (samples.len() / self.channels) as u64;
+		let pts = self.base_pts + (self.output_count - emitted) as i64;
+
+		frame.time = Time::new(1, self.target_rate);
+		frame.pts = pts;
+		if let Some(audio) = frame.audio_mut() {
+			audio.sample_rate = self.target_rate;
+			audio.write_f32(&samples)?;
+		}
+		Ok(frame)
+	}
+}
+
+impl Transform for Resample {
+	fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>> {
+		let Some(audio) = frame.audio() else {
+			return Ok(vec![frame]);
+		};
+
+		if self.source_rate == 0 {
+			let (sample_rate, channels) = (audio.sample_rate, audio.channels);
+			self.init(&frame, sample_rate, channels);
+		}
+		if self.source_rate == self.target_rate {
+			return Ok(vec![frame]);
+		}
+
+		let samples = audio.to_f32()?;
+		for (index, sample) in samples.iter().enumerate() {
+			self.buffers[index % self.channels].push(*sample);
+		}
+		self.input_count += (samples.len() / self.channels) as u64;
+		self.template = Some(frame);
+
+		let output = self.produce(false);
+		if output.is_empty() {
+			return Ok(Vec::new());
+		}
+		Ok(vec![self.emit(output)?])
+	}
+
+	fn latency(&self) -> Duration {
+		if self.source_rate == 0 {
+			return Duration::ZERO;
+		}
+		Duration::from_secs_f64(self.half_width() as f64 / self.source_rate as f64)
+	}
+
+	fn flush(&mut self) -> Result<Vec<Frame>> {
+		if self.template.is_none() || self.source_rate == self.target_rate {
+			return Ok(Vec::new());
+		}
+
+		let output = self.produce(true);
+		if output.is_empty() {
+			return Ok(Vec::new());
+		}
+		Ok(vec![self.emit(output)?])
+	}
+
+	fn name(&self) -> &'static str {
+		"resample"
+	}
+}
+
+fn sinc(x: f64) -> f64 {
+	if x == 0.0 {
+		return 1.0;
+	}
+	let x = std::f64::consts::PI * x;
+	x.sin() / x
+}
+
+fn bessel_i0(x: f64) -> f64 {
+	let mut sum = 1.0;
+	let mut term = 1.0;
+	let half = x / 2.0;
+	for k in 1..50 {
+		term *= (half / k as f64) * (half / k as f64);
+		sum += term;
+		if term < sum * 1e-12 {
+			break;
+		}
+	}
+	sum
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+
+	fn sine_frame(sample_rate: u32, frequency: f64, count: usize, offset: usize) -> Frame {
+		let data = (offset..offset + count)
+			.flat_map(|n| {
+				let t = n as f64 / sample_rate as f64;
+				let value = (0.5 * (2.0 * std::f64::consts::PI * frequency * t).sin() * 32767.0) as i16;
+				value.to_le_bytes()
+			})
+			.collect();
+		let audio = FrameAudio::new(data, sample_rate, 1, AudioFormat::PCM16);
+		Frame::new_audio(audio, Time::new(1, sample_rate), 0, 0).with_pts(offset as i64)
+	}
+
+	fn run(resample: &mut Resample, from: u32, frames: usize, size: usize) -> Vec<Frame> {
+		let mut output = Vec::new();
+		for index in 0..frames {
+			output.extend(resample.apply(sine_frame(from, 1000.0, size, index * size)).unwrap());
+		}
+		output.extend(resample.flush().unwrap());
+		assert!(resample.flush().unwrap().is_empty());
+		output
+	}
+
+	#[test]
+	fn test_output_length_and_timestamps() {
+		for (from, to) in [(44100, 48000), (96000, 22050)] {
+			let mut resample = Resample::new(to);
+			let frames = run(&mut resample, from, 10, 4410);
+
+			let total: usize = frames.iter().map(|f| f.audio().unwrap().nb_samples).sum();
+			let expected = (44100u64 * to as u64).div_ceil(from as u64) as usize;
+			assert_eq!(total, expected);
+
+			let mut pts = 0;
+			for frame in &frames {
+				assert_eq!(frame.pts, pts);
+				assert_eq!(frame.time, Time::new(1, to));
+				pts += frame.audio().unwrap().nb_samples as i64;
+			}
+		}
+	}
+
+	#[test]
+	fn test_sine_survives_conversion() {
+		let mut resample = Resample::new(48000).with_quality(ResampleQuality::High);
+		let frames = run(&mut resample, 44100, 4, 4410);
+		let samples: Vec<f32> =
+			frames.iter().flat_map(|f| f.audio().unwrap().to_f32().unwrap()).collect();
+
+		// compare against the ideal 1kHz sine away from the edges
+		for (n, sample) in samples.iter().enumerate().skip(1000).take(10000) {
+			let t = n as f64 / 48000.0;
+			let expected = 0.5 * (2.0 * std::f64::consts::PI * 1000.0 * t).sin();
+			assert!((*sample as f64 - expected).abs() < 0.01, "sample {} off: {}", n, sample);
+		}
+	}
+
+	#[test]
+	fn test_fast_mode_and_passthrough() {
+		let mut fast = Resample::new(22050).with_quality(ResampleQuality::Fast);
+		let frames = run(&mut fast, 44100, 2, 1000);
+		let total: usize = frames.iter().map(|f| f.audio().unwrap().nb_samples).sum();
+		assert_eq!(total, 1000);
+
+		let mut same = Resample::new(44100);
+		let frames = run(&mut same, 44100, 2, 1000);
+		assert_eq!(frames.len(), 2);
+	}
+}
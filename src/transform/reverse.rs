@@ -0,0 +1,264 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::core::Transform;
+use crate::core::frame::Frame;
+use crate::core::time::{Time, rescale};
+use crate::io::{Error, Result};
+
+const DEFAULT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+const OUTPUT_SAMPLES: usize = 4096;
+
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// raw sample data that no longer fits in memory, removed on drop
+struct Spill {
+	file: fs::File,
+	path: PathBuf,
+	length: u64,
+}
+
+impl Spill {
+	fn create() -> Result<Self> {
+		let id = SPILL_COUNTER.fetch_add(1, Ordering::Relaxed);
+		let name = format!("ffmpreg-reverse-{}-{}.raw", std::process::id(), id);
+		let path = std::env::temp_dir().join(name);
+		let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+		Ok(Self { file, path, length: 0 })
+	}
+
+	fn append(&mut self, data: &[u8]) -> Result<()> {
+		self.file.seek(SeekFrom::End(0))?;
+		self.file.write_all(data)?;
+		self.length += data.len() as u64;
+		Ok(())
+	}
+
+	fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+		self.file.seek(SeekFrom::Start(offset))?;
+		self.file.read_exact(buf)?;
+		Ok(())
+	}
+}
+
+impl Drop for Spill {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+// holds the whole stream and plays it back from the end once flushed
+pub struct Reverse {
+	memory_limit: usize,
+	buffer: Vec<u8>,
+	spill: Option<Spill>,
+	template: Option<Frame>,
+	frame_size: usize,
+	base_pts: i64,
+	remaining: Option<u64>,
+	emitted: u64,
+}
+
+impl Reverse {
+	pub fn new() -> Self {
+		Self {
+			memory_limit: DEFAULT_MEMORY_LIMIT,
+			buffer: Vec::new(),
+			spill: None,
+			template: None,
+			frame_size: 0,
+			base_pts: 0,
+			remaining: None,
+			emitted: 0,
+		}
+	}
+
+	// bytes kept in memory before the buffered samples move to a temp file
+	pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+		self.memory_limit = bytes;
+		self
+	}
+
+	fn total(&self) -> u64 {
+		self.spill.as_ref().map_or(0, |spill| spill.length) + self.buffer.len() as u64
+	}
+
+	fn spill_buffer(&mut self) -> Result<()> {
+		if self.spill.is_none() {
+			self.spill = Some(Spill::create()?);
+		}
+		if let Some(spill) = self.spill.as_mut() {
+			spill.append(&self.buffer)?;
+		}
+		self.buffer.clear();
+		Ok(())
+	}
+
+	// bytes [offset, offset + length) of the stream, across the temp file and the memory buffer
+	fn read_range(&mut self, offset: u64, length: usize) -> Result<Vec<u8>> {
+		let mut data = vec![0u8; length];
+		let spilled = self.spill.as_ref().map_or(0, |spill| spill.length);
+
+		let from_file = spilled.saturating_sub(offset).min(length as u64) as usize;
+		if from_file > 0
+			&& let Some(spill) = self.spill.as_mut()
+		{
+			spill.read_at(offset, &mut data[..from_file])?;
+		}
+
+		let memory_offset = (offset + from_file as u64).saturating_sub(spilled) as usize;
+		let rest = length - from_file;
+		data[from_file..].copy_from_slice(&self.buffer[memory_offset..memory_offset + rest]);
+		Ok(data)
+	}
+}
+
+impl Default for Reverse {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Transform for Reverse {
+	fn apply(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		let pts = frame.pts;
+		let time = frame.time;
+		let Some(audio) = frame.audio_mut() else {
+			return Ok(vec![frame]);
+		};
+
+		let frame_size = audio.bytes_per_sample() * std::cmp::max(1, audio.channels as usize);
+		let sample_rate = audio.sample_rate;
+		let data = std::mem::take(&mut audio.data);
+		audio.nb_samples = 0;
+
+		match &self.template {
+			None => {
+				self.frame_size = frame_size;
+				self.base_pts = rescale(pts, time, Time::new(1, sample_rate)).max(0);
+				self.template = Some(frame.clone());
+			}
+			Some(_) if frame_size != self.frame_size => {
+				return Err(Error::invalid_data("reverse input changed format mid-stream"));
+			}
+			Some(_) => {}
+		}
+
+		self.buffer.extend_from_slice(&data);
+		if self.buffer.len() > self.memory_limit {
+			self.spill_buffer()?;
+		}
+
+		// nothing comes out until the end of the stream
+		Ok(Vec::new())
+	}
+
+	fn latency(&self) -> Duration {
+		let Some(audio) = self.template.as_ref().and_then(|frame| frame.audio()) else {
+			return Duration::ZERO;
+		};
+		let samples = self.total() / self.frame_size as u64;
+		Duration::from_secs_f64(samples as f64 / audio.sample_rate as f64)
+	}
+
+	// one frame per call, read back from the end of the stream
+	fn flush(&mut self) -> Result<Vec<Frame>> {
+		let Some(template) = self.template.clone() else {
+			return Ok(Vec::new());
+		};
+
+		let total = self.total();
+		let remaining = *self.remaining.get_or_insert(total - total % self.frame_size as u64);
+		if remaining == 0 {
+			return Ok(Vec::new());
+		}
+
+		let length = std::cmp::min(remaining, (OUTPUT_SAMPLES * self.frame_size) as u64) as usize;
+		let offset = remaining - length as u64;
+		let data = self.read_range(offset, length)?;
+		let reversed: Vec<u8> = data.chunks_exact(self.frame_size).rev().flatten().copied().collect();
+
+		let mut frame = template;
+		let nb_samples = reversed.len() / self.frame_size;
+		let mut sample_rate = 0;
+		if let Some(audio) = frame.audio_mut() {
+			sample_rate = audio.sample_rate;
+			audio.data = reversed;
+			audio.nb_samples = nb_samples;
+		}
+		frame.time = Time::new(1, sample_rate);
+		frame.pts = self.base_pts + self.emitted as i64;
+
+		self.emitted += nb_samples as u64;
+		self.remaining = Some(offset);
+		Ok(vec![frame])
+	}
+
+	fn name(&self) -> &'static str {
+		"reverse"
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+
+	fn stereo_frame(pts: i64, count: usize) -> Frame {
+		let data = (pts..pts + count as i64)
+			.flat_map(|n| [n as i16, -(n as i16)])
+			.flat_map(|s| s.to_le_bytes())
+			.collect();
+		let audio = FrameAudio::new(data, 8000, 2, AudioFormat::PCM16);
+		Frame::new_audio(audio, Time::new(1, 8000), 0, 0).with_pts(pts)
+	}
+
+	fn reverse_all(mut reverse: Reverse, frames: usize, size: usize) -> Vec<Frame> {
+		for index in 0..frames {
+			assert!(reverse.apply(stereo_frame((index * size) as i64, size)).unwrap().is_empty());
+		}
+		let mut output = Vec::new();
+		loop {
+			let frames = reverse.flush().unwrap();
+			if frames.is_empty() {
+				return output;
+			}
+			output.extend(frames);
+		}
+	}
+
+	fn check(frames: &[Frame], total: usize) {
+		let samples: Vec<i16> = frames
+			.iter()
+			.flat_map(|f| {
+				f.audio().unwrap().data.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]]))
+			})
+			.collect();
+		assert_eq!(samples.len(), total * 2);
+		for (index, pair) in samples.chunks_exact(2).enumerate() {
+			let expected = (total - 1 - index) as i16;
+			assert_eq!(pair, [expected, -expected]);
+		}
+
+		let mut pts = 0;
+		for frame in frames {
+			assert_eq!(frame.pts, pts);
+			pts += frame.audio().unwrap().nb_samples as i64;
+		}
+	}
+
+	#[test]
+	fn test_reverses_in_memory() {
+		let frames = reverse_all(Reverse::new(), 3, 5000);
+		check(&frames, 15000);
+	}
+
+	#[test]
+	fn test_reverses_through_spill_file() {
+		let frames = reverse_all(Reverse::new().with_memory_limit(1000), 7, 900);
+		check(&frames, 6300);
+	}
+}
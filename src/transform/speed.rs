@@ -0,0 +1,425 @@
+use std::time::Duration;
+
+use super::resample::Resample;
+use crate::core::Transform;
+use crate::core::frame::Frame;
+use crate::core::time::{Time, rescale};
+use crate::io::{Error, Result};
+
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 4.0;
+const WINDOW_SECONDS: f64 = 0.03;
+const TOLERANCE_SECONDS: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpeedMode {
+	// time-stretch, pitch is kept
+	#[default]
+	Tempo,
+	// plays the samples faster or slower, pitch follows
+	Resample,
+}
+
+impl SpeedMode {
+	pub fn parse(value: &str) -> Result<Self> {
+		match value.to_lowercase().as_str() {
+			"tempo" | "wsola" => Ok(SpeedMode::Tempo),
+			"resample" | "pitch" => Ok(SpeedMode::Resample),
+			_ => Err(Error::invalid_data(format!("invalid speed mode '{}'", value))),
+		}
+	}
+}
+
+pub struct Speed {
+	factor: f64,
+	mode: SpeedMode,
+	stretch: Option<Wsola>,
+	resample: Option<(u32, Resample)>,
+}
+
+impl Speed {
+	pub fn new(factor: f64) -> Result<Self> {
+		if !(MIN_SPEED..=MAX_SPEED).contains(&factor) {
+			let message =
+				format!("speed must be between {}x and {}x, got {}", MIN_SPEED, MAX_SPEED, factor);
+			return Err(Error::invalid_data(message));
+		}
+		Ok(Self { factor, mode: SpeedMode::default(), stretch: None, resample: None })
+	}
+
+	// "1.5", "0.75x" or "1.5,mode=resample"
+	pub fn parse(value: &str) -> Result<Self> {
+		let mut parts = value.split(',');
+		let factor = parts.next().unwrap_or_default().trim().to_lowercase();
+		let factor = factor.strip_suffix('x').unwrap_or(&factor);
+		let factor = factor
+			.parse::<f64>()
+			.map_err(|_| Error::invalid_data(format!("invalid speed '{}'", value)))?;
+		let mut speed = Self::new(factor)?;
+
+		for part in parts {
+			match part.split_once('=') {
+				Some(("mode", mode)) => speed = speed.with_mode(SpeedMode::parse(mode.trim())?),
+				_ => return Err(Error::invalid_data(format!("unknown speed option '{}'", part))),
+			}
+		}
+		Ok(speed)
+	}
+
+	pub fn with_mode(mut self, mode: SpeedMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	pub fn factor(&self) -> f64 {
+		self.factor
+	}
+
+	fn apply_resample(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		let time = frame.time;
+		let pts = frame.pts;
+		let factor = self.factor;
+		let Some(audio) = frame.audio_mut() else {
+			return Ok(vec![frame]);
+		};
+
+		// relabel the samples as if they were recorded at a different rate, then convert back
+		let rate = audio.sample_rate;
+		let (played_rate, resample) = self.resample.get_or_insert_with(|| {
+			let played_rate = std::cmp::max(1, (rate as f64 * factor).round() as u32);
+			(played_rate, Resample::new(rate))
+		});
+		audio.sample_rate = *played_rate;
+		frame.pts = rescale(pts, time, Time::new(1, rate));
+		frame.time = Time::new(1, *played_rate);
+		resample.apply(frame)
+	}
+}
+
+impl Transform for Speed {
+	fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>> {
+		if frame.audio().is_none() || self.factor == 1.0 {
+			return Ok(vec![frame]);
+		}
+
+		match self.mode {
+			SpeedMode::Resample => self.apply_resample(frame),
+			SpeedMode::Tempo => {
+				let factor = self.factor;
+				self.stretch.get_or_insert_with(|| Wsola::new(factor)).apply(frame)
+			}
+		}
+	}
+
+	fn latency(&self) -> Duration {
+		if let Some((_, resample)) = &self.resample {
+			return resample.latency();
+		}
+		self.stretch.as_ref().map_or(Duration::ZERO, |stretch| stretch.latency())
+	}
+
+	fn flush(&mut self) -> Result<Vec<Frame>> {
+		if let Some((_, resample)) = self.resample.as_mut() {
+			return resample.flush();
+		}
+		match self.stretch.as_mut() {
+			Some(stretch) => stretch.flush(),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		"speed"
+	}
+}
+
+// waveform similarity overlap-add: hann windows taken at `factor` times the output hop,
+// each shifted within a tolerance to line up with the natural continuation of the previous one
+struct Wsola {
+	factor: f64,
+	channels: usize,
+	window: Vec<f32>,
+	hop: usize,
+	tolerance: usize,
+	input: Vec<f32>,
+	input_start: u64,
+	input_count: u64,
+	segment: u64,
+	previous: Option<u64>,
+	overlap: Vec<f32>,
+	output_count: u64,
+	base_pts: i64,
+	template: Option<Frame>,
+}
+
+impl Wsola {
+	fn new(factor: f64) -> Self {
+		Self {
+			factor,
+			channels: 0,
+			window: Vec::new(),
+			hop: 0,
+			tolerance: 0,
+			input: Vec::new(),
+			input_start: 0,
+			input_count: 0,
+			segment: 0,
+			previous: None,
+			overlap: Vec::new(),
+			output_count: 0,
+			base_pts: 0,
+			template: None,
+		}
+	}
+
+	fn init(&mut self, frame: &Frame, sample_rate: u32, channels: u8) {
+		let length = std::cmp::max(4, (sample_rate as f64 * WINDOW_SECONDS) as usize & !1);
+		self.channels = std::cmp::max(1, channels as usize);
+		self.hop = length / 2;
+		self.tolerance = std::cmp::max(1, (sample_rate as f64 * TOLERANCE_SECONDS) as usize);
+		self.window = (0..length)
+			.map(|n| {
+				let phase = 2.0 * std::f64::consts::PI * n as f64 / length as f64;
+				(0.5 - 0.5 * phase.cos()) as f32
+			})
+			.collect();
+		self.overlap = vec![0.0; self.hop * self.channels];
+
+		let input_pts = rescale(frame.pts, frame.time, Time::new(1, sample_rate));
+		self.base_pts = (input_pts as f64 / self.factor).round() as i64;
+	}
+
+	fn sample(&self, position: u64, channel: usize) -> f32 {
+		if position < self.input_start {
+			return 0.0;
+		}
+		let index = (position - self.input_start) as usize * self.channels + channel;
+		self.input.get(index).copied().unwrap_or(0.0)
+	}
+
+	fn nominal(&self, segment: u64) -> u64 {
+		(segment as f64 * self.hop as f64 * self.factor).round() as u64
+	}
+
+	fn target_length(&self) -> u64 {
+		(self.input_count as f64 / self.factor).round() as u64
+	}
+
+	// last input position the next segment may read
+	fn needed(&self) -> u64 {
+		let nominal = self.nominal(self.segment) + self.tolerance as u64;
+		let natural = self.previous.map_or(0, |previous| previous + self.hop as u64);
+		nominal.max(natural) + self.window.len() as u64
+	}
+
+	fn similarity(&self, candidate: u64, natural: u64) -> f32 {
+		let mut sum = 0.0;
+		// every other sample is plenty to find the best alignment
+		for n in (0..self.window.len() as u64).step_by(2) {
+			for channel in 0..self.channels {
+				sum += self.sample(candidate + n, channel) * self.sample(natural + n, channel);
+			}
+		}
+		sum
+	}
+
+	fn choose_position(&self) -> u64 {
+		let nominal = self.nominal(self.segment);
+		let Some(previous) = self.previous else {
+			return nominal;
+		};
+
+		let natural = previous + self.hop as u64;
+		let first = nominal.saturating_sub(self.tolerance as u64);
+		let last = nominal + self.tolerance as u64;
+
+		let mut best = (nominal, f32::MIN);
+		for candidate in first..=last {
+			let score = self.similarity(candidate, natural);
+			if score > best.1 {
+				best = (candidate, score);
+			}
+		}
+		best.0
+	}
+
+	// overlap-adds one segment and returns the hop that is now final
+	fn step(&mut self) -> Vec<f32> {
+		let position = self.choose_position();
+		let length = self.window.len();
+		let mut output = Vec::with_capacity(self.hop * self.channels);
+
+		for n in 0..length {
+			// the very first segment starts at full level instead of fading in
+			let weight = if self.previous.is_none() && n < self.hop { 1.0 } else { self.window[n] };
+			for channel in 0..self.channels {
+				let value = self.sample(position + n as u64, channel) * weight;
+				if n < self.hop {
+					output.push(self.overlap[n * self.channels + channel] + value);
+				} else {
+					self.overlap[(n - self.hop) * self.channels + channel] = value;
+				}
+			}
+		}
+
+		self.previous = Some(position);
+		self.segment += 1;
+		self.discard_consumed();
+		output
+	}
+
+	fn discard_consumed(&mut self) {
+		let nominal = self.nominal(self.segment).saturating_sub(self.tolerance as u64);
+		let natural = self.previous.map_or(0, |previous| previous + self.hop as u64);
+		let keep_from = nominal.min(natural);
+		if keep_from <= self.input_start {
+			return;
+		}
+		let drop =
+			std::cmp::min((keep_from - self.input_start) as usize * self.channels, self.input.len());
+		self.input.drain(..drop);
+		self.input_start += (drop / self.channels) as u64;
+	}
+
+	fn emit(&mut self, mut samples: Vec<f32>) -> Result<Frame> {
+		let mut frame =
+			self.template.clone().ok_or_else(|| Error::invalid_data("speed has no input"))?;
+
+		let limit = self.target_length().saturating_sub(self.output_count) as usize * self.channels;
+		samples.truncate(limit);
+
+		let pts = self.base_pts + self.output_count as i64;
+		self.output_count += (samples.len() / self.channels) as u64;
+
+		let mut sample_rate = 0;
+		if let Some(audio) = frame.audio_mut() {
+			sample_rate = audio.sample_rate;
+			audio.write_f32(&samples)?;
+		}
+		frame.time = Time::new(1, sample_rate);
+		frame.pts = pts;
+		Ok(frame)
+	}
+
+	fn latency(&self) -> Duration {
+		let Some(audio) = self.template.as_ref().and_then(|frame| frame.audio()) else {
+			return Duration::ZERO;
+		};
+		let samples = self.window.len() + self.tolerance;
+		Duration::from_secs_f64(samples as f64 / audio.sample_rate as f64)
+	}
+
+	fn apply(&mut self, frame: Frame) -> Result<Vec<Frame>> {
+		let Some(audio) = frame.audio() else {
+			return Ok(vec![frame]);
+		};
+
+		if self.template.is_none() {
+			let (sample_rate, channels) = (audio.sample_rate, audio.channels);
+			self.init(&frame, sample_rate, channels);
+		}
+
+		let samples = audio.to_f32()?;
+		self.input_count += (samples.len() / self.channels) as u64;
+		self.input.extend_from_slice(&samples);
+		self.template = Some(frame);
+
+		let mut output = Vec::new();
+		while self.needed() <= self.input_count {
+			output.extend(self.step());
+		}
+		if output.is_empty() {
+			return Ok(Vec::new());
+		}
+		Ok(vec![self.emit(output)?])
+	}
+
+	fn flush(&mut self) -> Result<Vec<Frame>> {
+		if self.template.is_none() || self.output_count >= self.target_length() {
+			return Ok(Vec::new());
+		}
+
+		let mut output = Vec::new();
+		let pending = self.target_length() - self.output_count;
+		while ((output.len() / self.channels) as u64) < pending {
+			output.extend(self.step());
+		}
+		Ok(vec![self.emit(output)?])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+
+	fn sine_frame(sample_rate: u32, frequency: f64, count: usize, offset: usize) -> Frame {
+		let samples: Vec<f32> = (offset..offset + count)
+			.map(|n| {
+				(0.5 * (2.0 * std::f64::consts::PI * frequency * n as f64 / sample_rate as f64).sin())
+					as f32
+			})
+			.collect();
+		let mut audio = FrameAudio::new(Vec::new(), sample_rate, 1, AudioFormat::PCM32);
+		audio.write_f32(&samples).unwrap();
+		Frame::new_audio(audio, Time::new(1, sample_rate), 0, 0).with_pts(offset as i64)
+	}
+
+	fn run(speed: &mut Speed, sample_rate: u32, frames: usize, size: usize) -> Vec<Frame> {
+		let mut output = Vec::new();
+		for index in 0..frames {
+			output.extend(speed.apply(sine_frame(sample_rate, 400.0, size, index * size)).unwrap());
+		}
+		output.extend(speed.flush().unwrap());
+		assert!(speed.flush().unwrap().is_empty());
+		output
+	}
+
+	// dominant period from the zero crossings of the middle of the signal
+	fn zero_crossings(samples: &[f32]) -> usize {
+		samples.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count()
+	}
+
+	#[test]
+	fn test_tempo_keeps_pitch_and_scales_length() {
+		for factor in [0.5, 1.5, 2.0] {
+			let mut speed = Speed::new(factor).unwrap();
+			let frames = run(&mut speed, 8000, 8, 1000);
+
+			let samples: Vec<f32> =
+				frames.iter().flat_map(|f| f.audio().unwrap().to_f32().unwrap()).collect();
+			assert_eq!(samples.len(), (8000.0 / factor).round() as usize);
+
+			// 400Hz over the middle half second still crosses zero about 200 times
+			let middle = &samples[samples.len() / 2 - 2000..samples.len() / 2 + 2000];
+			let crossings = zero_crossings(middle);
+			assert!((195..=205).contains(&crossings), "{}x gave {} crossings", factor, crossings);
+
+			let mut pts = 0;
+			for frame in &frames {
+				assert_eq!(frame.pts, pts);
+				pts += frame.audio().unwrap().nb_samples as i64;
+			}
+		}
+	}
+
+	#[test]
+	fn test_resample_mode_shifts_pitch() {
+		let mut speed = Speed::parse("2x,mode=resample").unwrap();
+		let frames = run(&mut speed, 8000, 8, 1000);
+		let samples: Vec<f32> =
+			frames.iter().flat_map(|f| f.audio().unwrap().to_f32().unwrap()).collect();
+		assert_eq!(samples.len(), 4000);
+		assert_eq!(frames[0].time, Time::new(1, 8000));
+
+		let crossings = zero_crossings(&samples[1000..3000]);
+		assert!((198..=202).contains(&crossings), "{} crossings", crossings);
+	}
+
+	#[test]
+	fn test_range_is_checked() {
+		assert!(Speed::parse("5").is_err());
+		assert!(Speed::parse("0.1").is_err());
+		assert!(Speed::parse("1.5,mode=chipmunk").is_err());
+	}
+}
@@ -0,0 +1,159 @@
+use crate::core::Transform;
+use crate::core::frame::Frame;
+use crate::core::time::{Time, Timestamp, rescale};
+use crate::io::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrimEnd {
+	At(Timestamp),
+	After(Timestamp),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Trim {
+	start: Timestamp,
+	end: Option<TrimEnd>,
+}
+
+impl Trim {
+	pub fn new(start: Timestamp, end: Option<TrimEnd>) -> Self {
+		Self { start, end }
+	}
+
+	// "00:01:02.5-00:03:00", "10s-" or "start=10s,duration=30s" (also end=)
+	pub fn parse(value: &str) -> Result<Self> {
+		let invalid = || Error::invalid_data(format!("invalid trim '{}'", value));
+
+		if !value.contains('=') {
+			let (start, end) = value.split_once('-').unwrap_or((value, ""));
+			let start = match start.trim() {
+				"" => Timestamp::Seconds(0.0),
+				start => Timestamp::parse(start)?,
+			};
+			let end = match end.trim() {
+				"" => None,
+				end => Some(TrimEnd::At(Timestamp::parse(end)?)),
+			};
+			return Ok(Self::new(start, end));
+		}
+
+		let mut trim = Self::new(Timestamp::Seconds(0.0), None);
+		for part in value.split(',') {
+			let (key, time) = part.split_once('=').ok_or_else(invalid)?;
+			let time = Timestamp::parse(time)?;
+			match key.trim() {
+				"start" => trim.start = time,
+				"end" if trim.end.is_none() => trim.end = Some(TrimEnd::At(time)),
+				"duration" if trim.end.is_none() => trim.end = Some(TrimEnd::After(time)),
+				_ => return Err(invalid()),
+			}
+		}
+		Ok(trim)
+	}
+
+	// first kept sample and the sample after the last kept one
+	pub fn range(&self, sample_rate: u32) -> Result<(u64, Option<u64>)> {
+		let start = self.start.to_samples(sample_rate);
+		let end = match self.end {
+			Some(TrimEnd::At(end)) => Some(end.to_samples(sample_rate)),
+			Some(TrimEnd::After(duration)) => Some(start + duration.to_samples(sample_rate)),
+			None => None,
+		};
+
+		if let Some(end) = end
+			&& end <= start
+		{
+			return Err(Error::invalid_data("trim end must be after its start"));
+		}
+		Ok((start, end))
+	}
+}
+
+impl Transform for Trim {
+	fn apply(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		let position = frame.pts;
+		let time = frame.time;
+		let Some(audio) = frame.audio_mut() else {
+			return Ok(vec![frame]);
+		};
+
+		let sample_rate = audio.sample_rate;
+		let (start, end) = self.range(sample_rate)?;
+		let first = std::cmp::max(0, rescale(position, time, Time::new(1, sample_rate))) as u64;
+		let last = first + audio.nb_samples as u64;
+
+		let keep_from = first.clamp(start, std::cmp::max(start, last));
+		let keep_to = end.map_or(last, |end| last.min(end)).max(keep_from);
+		if keep_to == keep_from {
+			return Ok(Vec::new());
+		}
+
+		let frame_size = audio.bytes_per_sample() * audio.channels as usize;
+		let length = audio.data.len();
+		let from = std::cmp::min(length, (keep_from - first) as usize * frame_size);
+		let to = std::cmp::min(length, (keep_to - first) as usize * frame_size).max(from);
+
+		if from > 0 || to < length {
+			audio.data = audio.data[from..to].to_vec();
+			audio.nb_samples = audio.data.len() / std::cmp::max(1, frame_size);
+		}
+
+		frame.time = Time::new(1, sample_rate);
+		frame.pts = (keep_from - start) as i64;
+		Ok(vec![frame])
+	}
+
+	fn name(&self) -> &'static str {
+		"trim"
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+
+	fn frame(pts: i64, count: usize) -> Frame {
+		let data = (pts..pts + count as i64).flat_map(|n| (n as i16).to_le_bytes()).collect();
+		let audio = FrameAudio::new(data, 1000, 1, AudioFormat::PCM16);
+		Frame::new_audio(audio, Time::new(1, 1000), 0, 0).with_pts(pts)
+	}
+
+	fn samples(frame: &Frame) -> Vec<i16> {
+		let data = &frame.audio().unwrap().data;
+		data.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+	}
+
+	#[test]
+	fn test_parse_forms() {
+		let trim = Trim::parse("00:00:01.5-00:00:03").unwrap();
+		assert_eq!(trim.range(1000).unwrap(), (1500, Some(3000)));
+
+		let trim = Trim::parse("start=10s,duration=30s").unwrap();
+		assert_eq!(trim.range(100).unwrap(), (1000, Some(4000)));
+
+		let trim = Trim::parse("start=100samples").unwrap();
+		assert_eq!(trim.range(44100).unwrap(), (100, None));
+
+		assert!(Trim::parse("3s-1s").unwrap().range(1000).is_err());
+		assert!(Trim::parse("begin=1s").is_err());
+	}
+
+	#[test]
+	fn test_cuts_inside_frames_and_rebases_pts() {
+		let mut trim = Trim::parse("105samples-230samples").unwrap();
+
+		assert!(trim.apply(frame(0, 100)).unwrap().is_empty());
+
+		let first = trim.apply(frame(100, 100)).unwrap().remove(0);
+		assert_eq!(first.pts, 0);
+		assert_eq!(samples(&first).first(), Some(&105));
+		assert_eq!(first.audio().unwrap().nb_samples, 95);
+
+		let last = trim.apply(frame(200, 100)).unwrap().remove(0);
+		assert_eq!(last.pts, 95);
+		assert_eq!(samples(&last).last(), Some(&229));
+
+		assert!(trim.apply(frame(300, 100)).unwrap().is_empty());
+	}
+}
@@ -1,25 +1,163 @@
 use crate::core::Transform;
 use crate::core::frame::Frame;
-use crate::io::Result;
+use crate::io::{Error, Result};
+
+// soft clipping starts bending samples above this level
+const SOFT_KNEE: f32 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipMode {
+	#[default]
+	Hard,
+	Soft,
+	Error,
+}
+
+impl ClipMode {
+	pub fn parse(value: &str) -> Result<Self> {
+		match value.to_lowercase().as_str() {
+			"hard" => Ok(ClipMode::Hard),
+			"soft" => Ok(ClipMode::Soft),
+			"error" => Ok(ClipMode::Error),
+			_ => Err(Error::invalid_data(format!("invalid clip mode '{}'", value))),
+		}
+	}
+
+	pub fn clip(&self, sample: f32) -> Result<f32> {
+		match self {
+			ClipMode::Hard => Ok(sample.clamp(-1.0, 1.0)),
+			ClipMode::Soft => Ok(soft_clip(sample)),
+			ClipMode::Error if sample.abs() > 1.0 => {
+				Err(Error::invalid_data(format!("sample {:.3} exceeds full scale", sample)))
+			}
+			ClipMode::Error => Ok(sample),
+		}
+	}
+}
+
+fn soft_clip(sample: f32) -> f32 {
+	let magnitude = sample.abs();
+	if magnitude <= SOFT_KNEE {
+		return sample;
+	}
+	let range = 1.0 - SOFT_KNEE;
+	let bent = SOFT_KNEE + range * ((magnitude - SOFT_KNEE) / range).tanh();
+	bent.copysign(sample)
+}
+
+pub fn db_to_linear(db: f32) -> f32 {
+	10f32.powf(db / 20.0)
+}
+
+pub fn linear_to_db(factor: f32) -> f32 {
+	20.0 * factor.log10()
+}
+
+// accepts a linear factor ("1.5") or a gain in decibels ("+6dB", "-3db")
+pub fn parse_gain(value: &str) -> Result<f32> {
+	let value = value.trim();
+	let lower = value.to_lowercase();
+	let invalid = || Error::invalid_data(format!("invalid gain '{}'", value));
+
+	if let Some(db) = lower.strip_suffix("db") {
+		let db = db.trim().parse::<f32>().map_err(|_| invalid())?;
+		return Ok(db_to_linear(db));
+	}
+
+	let factor = lower.parse::<f32>().map_err(|_| invalid())?;
+	if !factor.is_finite() || factor < 0.0 {
+		return Err(invalid());
+	}
+	Ok(factor)
+}
 
-#[allow(dead_code)]
 pub struct Volume {
 	factor: f32,
+	clip: ClipMode,
 }
 
 impl Volume {
 	pub fn new(factor: f32) -> Self {
-		Self { factor }
+		Self { factor, clip: ClipMode::default() }
+	}
+
+	pub fn parse(value: &str) -> Result<Self> {
+		Ok(Self::new(parse_gain(value)?))
+	}
+
+	pub fn with_clip(mut self, clip: ClipMode) -> Self {
+		self.clip = clip;
+		self
+	}
+
+	pub fn factor(&self) -> f32 {
+		self.factor
 	}
 }
 
 impl Transform for Volume {
-	fn apply(&mut self, mut frame: Frame) -> Result<Frame> {
-		if let Some(_audio_frame) = frame.audio_mut() {}
-		Ok(frame)
+	fn apply(&mut self, mut frame: Frame) -> Result<Vec<Frame>> {
+		if let Some(audio) = frame.audio_mut() {
+			let mut samples = audio.to_f32()?;
+			for sample in samples.iter_mut() {
+				*sample = self.clip.clip(*sample * self.factor)?;
+			}
+			audio.write_f32(&samples)?;
+		}
+		Ok(vec![frame])
 	}
 
 	fn name(&self) -> &'static str {
 		"volume"
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::frame::{AudioFormat, FrameAudio};
+	use crate::core::time::Time;
+
+	fn pcm16_frame(samples: &[i16]) -> Frame {
+		let data = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+		let audio = FrameAudio::new(data, 44100, 1, AudioFormat::PCM16);
+		Frame::new_audio(audio, Time::new(1, 44100), 0, 0)
+	}
+
+	fn pcm16_samples(frame: &Frame) -> Vec<i16> {
+		let data = &frame.audio().unwrap().data;
+		data.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+	}
+
+	#[test]
+	fn test_parse_gain() {
+		assert_eq!(parse_gain("2.0").unwrap(), 2.0);
+		assert!((parse_gain("+6dB").unwrap() - 1.9953).abs() < 1e-3);
+		assert!((parse_gain("-3db").unwrap() - 0.7079).abs() < 1e-3);
+		assert!(parse_gain("loud").is_err());
+		assert!(parse_gain("-2").is_err());
+	}
+
+	#[test]
+	fn test_volume_scales_pcm16() {
+		let mut volume = Volume::new(0.5);
+		let frame = volume.apply(pcm16_frame(&[1000, -2000, 0])).unwrap().remove(0);
+		assert_eq!(pcm16_samples(&frame), vec![500, -1000, 0]);
+	}
+
+	#[test]
+	fn test_volume_clip_modes() {
+		let mut hard = Volume::new(4.0);
+		let frame = hard.apply(pcm16_frame(&[16000, -16000])).unwrap().remove(0);
+		assert_eq!(pcm16_samples(&frame), vec![32767, -32768]);
+
+		let mut soft = Volume::new(4.0).with_clip(ClipMode::Soft);
+		let frame = soft.apply(pcm16_frame(&[8000, 1000])).unwrap().remove(0);
+		let samples = pcm16_samples(&frame);
+		assert!(samples[0] > 26214 && samples[0] < 32000);
+		assert_eq!(samples[1], 4000);
+
+		let mut strict = Volume::new(4.0).with_clip(ClipMode::Error);
+		assert!(strict.apply(pcm16_frame(&[16000])).is_err());
+	}
+}